@@ -0,0 +1,350 @@
+use std::sync::LazyLock;
+
+use crate::Bitboard;
+use crate::Square;
+
+const FILE_A: u64 = 0x0101_0101_0101_0101;
+const FILE_H: u64 = FILE_A << 7;
+const RANK_1: u64 = 0xff;
+const RANK_8: u64 = RANK_1 << 56;
+
+const ROOK_DIRECTIONS: [(i8, i8); 4] = [(0, 1), (0, -1), (1, 0), (-1, 0)];
+const BISHOP_DIRECTIONS: [(i8, i8); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+/// Random number seeds for the magics of each rank, picked as Stockfish's are so a magic
+/// turns up in few tries
+const MAGIC_SEEDS: [u64; 8] = [728, 10316, 55013, 32803, 12281, 15100, 16645, 255];
+
+/// Squares a knight on each square attacks
+pub(crate) const KNIGHT_ATTACKS: [u64; 64] = leaper_table(Leaper::Knight);
+
+/// Squares a king on each square attacks
+pub(crate) const KING_ATTACKS: [u64; 64] = leaper_table(Leaper::King);
+
+/// Squares a pawn of each side on each square attacks
+pub(crate) const PAWN_ATTACKS: [[u64; 64]; 2] = [leaper_table(Leaper::WhitePawn), leaper_table(Leaper::BlackPawn)];
+
+/// Rook and bishop attacks for every square and blocker, built the first time they're
+/// needed
+pub(crate) static SLIDERS: LazyLock<SliderTables> = LazyLock::new(|| {
+    let backend = if has_pext() { SlidingBackend::Pext } else { SlidingBackend::Magic };
+    SliderTables::new(backend).expect("Internal error: sliding backend unavailable")
+});
+
+pub fn knight_attacks(square: Square) -> Bitboard {
+    KNIGHT_ATTACKS[square.to_u8() as usize].into()
+}
+
+pub fn king_attacks(square: Square) -> Bitboard {
+    KING_ATTACKS[square.to_u8() as usize].into()
+}
+
+/// The squares a pawn of `side` on `square` captures on
+pub fn pawn_attacks(side: usize, square: Square) -> Bitboard {
+    PAWN_ATTACKS[side][square.to_u8() as usize].into()
+}
+
+/// The squares a rook on `square` attacks, up to and including the first piece of
+/// `occupancy` in each direction
+pub fn rook_attacks(square: Square, occupancy: Bitboard) -> Bitboard {
+    SLIDERS.rook(square.to_u8(), occupancy.into()).into()
+}
+
+/// The squares a bishop on `square` attacks, see `rook_attacks`
+pub fn bishop_attacks(square: Square, occupancy: Bitboard) -> Bitboard {
+    SLIDERS.bishop(square.to_u8(), occupancy.into()).into()
+}
+
+pub fn queen_attacks(square: Square, occupancy: Bitboard) -> Bitboard {
+    rook_attacks(square, occupancy) | bishop_attacks(square, occupancy)
+}
+
+/// How the sliding attack tables are indexed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlidingBackend {
+    Magic,
+    /// BMI2 `pext`, with the `pext` feature on a CPU that has it
+    Pext,
+}
+
+/// The backend the sliding attack functions use on this machine
+pub fn sliding_backend() -> SlidingBackend {
+    match SLIDERS.pext {
+        true => SlidingBackend::Pext,
+        false => SlidingBackend::Magic,
+    }
+}
+
+/// Pieces whose attacks never depend on blockers
+enum Leaper {
+    Knight,
+    King,
+    WhitePawn,
+    BlackPawn,
+}
+
+const fn leaper_table(leaper: Leaper) -> [u64; 64] {
+    let mut table = [0; 64];
+
+    let mut sq = 0;
+    while sq < 64 {
+        let bb = 1 << sq;
+
+        table[sq] = match leaper {
+            Leaper::Knight => knight_attacks_from(bb),
+            Leaper::King => king_attacks_from(bb),
+            Leaper::WhitePawn => white_pawn_attacks_from(bb),
+            Leaper::BlackPawn => black_pawn_attacks_from(bb),
+        };
+
+        sq += 1;
+    }
+
+    table
+}
+
+const fn knight_attacks_from(bb: u64) -> u64 {
+    let l1 = (bb >> 1) & !FILE_H;
+    let l2 = (bb >> 2) & !(FILE_H | FILE_H >> 1);
+    let r1 = (bb << 1) & !FILE_A;
+    let r2 = (bb << 2) & !(FILE_A | FILE_A << 1);
+
+    let h1 = l1 | r1;
+    let h2 = l2 | r2;
+
+    h1 << 16 | h1 >> 16 | h2 << 8 | h2 >> 8
+}
+
+const fn king_attacks_from(bb: u64) -> u64 {
+    let row = bb | (bb << 1) & !FILE_A | (bb >> 1) & !FILE_H;
+
+    (row | row << 8 | row >> 8) & !bb
+}
+
+const fn white_pawn_attacks_from(bb: u64) -> u64 {
+    (bb << 7) & !FILE_H | (bb << 9) & !FILE_A
+}
+
+const fn black_pawn_attacks_from(bb: u64) -> u64 {
+    (bb >> 9) & !FILE_H | (bb >> 7) & !FILE_A
+}
+
+/// Walks out from `sq` in each direction until the edge or a blocker. Too slow for
+/// move generation, it fills in the tables.
+fn slider_attacks(sq: u8, occupancy: u64, directions: &[(i8, i8); 4]) -> u64 {
+    let mut attacks = 0;
+
+    for &(df, dr) in directions {
+        let mut file = (sq % 8) as i8 + df;
+        let mut rank = (sq / 8) as i8 + dr;
+
+        while (0..8).contains(&file) && (0..8).contains(&rank) {
+            let bb = 1u64 << (rank * 8 + file);
+            attacks |= bb;
+
+            if occupancy & bb != 0 { break; }
+
+            file += df;
+            rank += dr;
+        }
+    }
+
+    attacks
+}
+
+/// Where one square's attacks are in `SliderTables::attacks` and how to index them
+#[derive(Default, Clone, Copy)]
+struct Magic {
+    /// The squares whose blockers change the attacks, the edges never do
+    mask: u64,
+    /// Unused with `pext`
+    magic: u64,
+    shift: u32,
+    offset: usize,
+}
+
+impl Magic {
+    fn index(&self, occupancy: u64) -> usize {
+        self.offset + ((occupancy & self.mask).wrapping_mul(self.magic) >> self.shift) as usize
+    }
+}
+
+/// Magic bitboard attack tables
+///
+/// Multiplying the blockers on a square's mask by its magic number gathers them into
+/// the top bits, a perfect hash of every blocker arrangement with the same attacks. The
+/// magics are found by trial when the tables are first used, from fixed seeds so they're
+/// the same every run.
+///
+/// With the `pext` feature on a CPU with BMI2 the blockers are gathered by `pext`
+/// instead, which needs no magics and is faster again.
+///
+/// The attack functions share one set of tables for `sliding_backend`, building others
+/// is only useful to compare the backends.
+///
+/// https://www.chessprogramming.org/Magic_Bitboards
+/// https://www.chessprogramming.org/BMI2#PEXTBitboards
+pub struct SliderTables {
+    rook: [Magic; 64],
+    bishop: [Magic; 64],
+    attacks: Vec<u64>,
+    pext: bool,
+}
+
+impl SliderTables {
+    /// Builds the tables for `backend`, `None` for `Pext` without the `pext` feature or
+    /// without BMI2 on this CPU
+    pub fn new(backend: SlidingBackend) -> Option<Self> {
+        let pext = backend == SlidingBackend::Pext;
+
+        if pext && !has_pext() {
+            return None;
+        }
+
+        let mut tables = SliderTables {
+            rook: [Magic::default(); 64],
+            bishop: [Magic::default(); 64],
+            attacks: Vec::new(),
+            pext,
+        };
+
+        for sq in 0..64 {
+            let edges = ((RANK_1 | RANK_8) & !(RANK_1 << (sq / 8 * 8))) | ((FILE_A | FILE_H) & !(FILE_A << (sq % 8)));
+
+            let rook_mask = slider_attacks(sq, 0, &ROOK_DIRECTIONS) & !edges;
+            tables.rook[sq as usize] = tables.add_square(sq, rook_mask, &ROOK_DIRECTIONS);
+
+            let bishop_mask = slider_attacks(sq, 0, &BISHOP_DIRECTIONS) & !(RANK_1 | RANK_8 | FILE_A | FILE_H);
+            tables.bishop[sq as usize] = tables.add_square(sq, bishop_mask, &BISHOP_DIRECTIONS);
+        }
+
+        Some(tables)
+    }
+
+    /// See `rook_attacks`
+    pub fn rook_attacks(&self, square: Square, occupancy: Bitboard) -> Bitboard {
+        self.rook(square.to_u8(), occupancy.into()).into()
+    }
+
+    /// See `bishop_attacks`
+    pub fn bishop_attacks(&self, square: Square, occupancy: Bitboard) -> Bitboard {
+        self.bishop(square.to_u8(), occupancy.into()).into()
+    }
+
+    pub(crate) fn rook(&self, sq: u8, occupancy: u64) -> u64 {
+        self.attacks[self.index(&self.rook[sq as usize], occupancy)]
+    }
+
+    pub(crate) fn bishop(&self, sq: u8, occupancy: u64) -> u64 {
+        self.attacks[self.index(&self.bishop[sq as usize], occupancy)]
+    }
+
+    #[inline]
+    fn index(&self, magic: &Magic, occupancy: u64) -> usize {
+        #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+        if self.pext {
+            // SAFETY: `pext` is only set when the CPU has BMI2
+            return magic.offset + unsafe { pext(occupancy, magic.mask) } as usize;
+        }
+
+        magic.index(occupancy)
+    }
+
+    /// Finds a magic for `sq` and appends its attacks to the table
+    fn add_square(&mut self, sq: u8, mask: u64, directions: &[(i8, i8); 4]) -> Magic {
+        let bits = mask.count_ones();
+        let size = 1 << bits;
+
+        // Every subset of the mask, by the Carry-Rippler trick
+        let mut blockers = Vec::with_capacity(size);
+        let mut subset = 0u64;
+
+        loop {
+            blockers.push(subset);
+            subset = subset.wrapping_sub(mask) & mask;
+
+            if subset == 0 { break; }
+        }
+
+        let reference: Vec<u64> = blockers.iter()
+            .map(|&occupancy| slider_attacks(sq, occupancy, directions))
+            .collect();
+
+        let offset = self.attacks.len();
+
+        // Subsets come out of the Carry-Rippler in the order `pext` numbers them
+        if self.pext {
+            self.attacks.extend(reference);
+            return Magic { mask, offset, ..Magic::default() };
+        }
+
+        self.attacks.resize(offset + size, 0);
+
+        // Which attempt last wrote each entry, so the table needn't be cleared between them
+        let mut written = vec![0u32; size];
+        let mut attempt = 0;
+
+        let mut rng = MAGIC_SEEDS[sq as usize / 8];
+
+        loop {
+            attempt += 1;
+
+            // Few bits set make better magics
+            let magic = next_random(&mut rng) & next_random(&mut rng) & next_random(&mut rng);
+
+            // Magics that don't spread the mask over the top byte rarely work
+            if (mask.wrapping_mul(magic) >> 56).count_ones() < 6 {
+                continue;
+            }
+
+            let candidate = Magic { mask, magic, shift: 64 - bits, offset };
+            let table = &mut self.attacks[offset..];
+
+            let fits = blockers.iter().zip(&reference).all(|(&occupancy, &attacks)| {
+                let index = candidate.index(occupancy) - offset;
+
+                if written[index] != attempt {
+                    written[index] = attempt;
+                    table[index] = attacks;
+                    true
+                } else {
+                    // Two blocker sets may share an entry when their attacks are the same
+                    table[index] == attacks
+                }
+            });
+
+            if fits {
+                return candidate;
+            }
+        }
+    }
+}
+
+/// The bits of `value` under `mask`, packed into the low bits
+///
+/// # Safety
+///
+/// The CPU must have BMI2.
+#[cfg(all(feature = "pext", target_arch = "x86_64"))]
+#[target_feature(enable = "bmi2")]
+unsafe fn pext(value: u64, mask: u64) -> u64 {
+    std::arch::x86_64::_pext_u64(value, mask)
+}
+
+#[cfg(all(feature = "pext", target_arch = "x86_64"))]
+fn has_pext() -> bool {
+    std::arch::is_x86_feature_detected!("bmi2")
+}
+
+#[cfg(not(all(feature = "pext", target_arch = "x86_64")))]
+fn has_pext() -> bool {
+    false
+}
+
+/// xorshift64*
+fn next_random(state: &mut u64) -> u64 {
+    *state ^= *state >> 12;
+    *state ^= *state << 25;
+    *state ^= *state >> 27;
+    state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+}
@@ -3,11 +3,46 @@ use std::fmt::Debug;
 use crate::square::Square;
 
 pub const EMPTY: Bitboard = Bitboard(0);
+pub const FULL: Bitboard = Bitboard(!0);
+
+pub const FILE_A: Bitboard = Bitboard(0x0101_0101_0101_0101);
+pub const FILE_B: Bitboard = Bitboard(FILE_A.0 << 1);
+pub const FILE_C: Bitboard = Bitboard(FILE_A.0 << 2);
+pub const FILE_D: Bitboard = Bitboard(FILE_A.0 << 3);
+pub const FILE_E: Bitboard = Bitboard(FILE_A.0 << 4);
+pub const FILE_F: Bitboard = Bitboard(FILE_A.0 << 5);
+pub const FILE_G: Bitboard = Bitboard(FILE_A.0 << 6);
+pub const FILE_H: Bitboard = Bitboard(FILE_A.0 << 7);
+
+pub const RANK_1: Bitboard = Bitboard(0xff);
+pub const RANK_2: Bitboard = Bitboard(RANK_1.0 << 8);
+pub const RANK_3: Bitboard = Bitboard(RANK_1.0 << 16);
+pub const RANK_4: Bitboard = Bitboard(RANK_1.0 << 24);
+pub const RANK_5: Bitboard = Bitboard(RANK_1.0 << 32);
+pub const RANK_6: Bitboard = Bitboard(RANK_1.0 << 40);
+pub const RANK_7: Bitboard = Bitboard(RANK_1.0 << 48);
+pub const RANK_8: Bitboard = Bitboard(RANK_1.0 << 56);
+
+/// Indexed by file, a = 0
+pub const FILES: [Bitboard; 8] = [FILE_A, FILE_B, FILE_C, FILE_D, FILE_E, FILE_F, FILE_G, FILE_H];
+
+/// Indexed by rank, 1 = 0
+pub const RANKS: [Bitboard; 8] = [RANK_1, RANK_2, RANK_3, RANK_4, RANK_5, RANK_6, RANK_7, RANK_8];
+
+/// a1 to h8
+pub const DIAGONAL: Bitboard = Bitboard(0x8040_2010_0804_0201);
+
+/// a8 to h1
+pub const ANTI_DIAGONAL: Bitboard = Bitboard(0x0102_0408_1020_4080);
+
+pub const LIGHT_SQUARES: Bitboard = Bitboard(0x55AA_55AA_55AA_55AA);
+pub const DARK_SQUARES: Bitboard = Bitboard(!LIGHT_SQUARES.0);
 
 /// Bitboard Implementation
 /// 
 /// Layout/Bit-Ordering
 /// 
+/// ```text
 /// 7 | 63 62 61 60 59 58 57 56
 /// 6 | 55 54 53 52 51 50 49 48
 /// 5 | 47 46 45 44 43 42 41 40
@@ -18,6 +53,7 @@ pub const EMPTY: Bitboard = Bitboard(0);
 /// 0 | 07 06 05 04 03 02 01 00
 ///   ------------------------
 ///     0  1  2  3  4  5  6  7
+/// ```
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash)]
 pub struct Bitboard(u64);
@@ -58,6 +94,159 @@ impl Bitboard {
     pub fn flip(&mut self, square: Square) {
         self.0 ^= 1 << square.to_u8();
     }
+
+    /// The diagonal running from bottom left to top right through `square`
+    pub fn diagonal(square: Square) -> Bitboard {
+        let (file, rank) = (square.to_u8() % 8, square.to_u8() / 8);
+
+        match file >= rank {
+            true => Bitboard(DIAGONAL.0 >> (8 * (file - rank))),
+            false => Bitboard(DIAGONAL.0 << (8 * (rank - file))),
+        }
+    }
+
+    /// The diagonal running from top left to bottom right through `square`
+    pub fn anti_diagonal(square: Square) -> Bitboard {
+        let sum = square.to_u8() % 8 + square.to_u8() / 8;
+
+        match sum >= 7 {
+            true => Bitboard(ANTI_DIAGONAL.0 << (8 * (sum - 7))),
+            false => Bitboard(ANTI_DIAGONAL.0 >> (8 * (7 - sum))),
+        }
+    }
+
+    /// The lowest set square, a1 first
+    pub fn lsb(&self) -> Option<Square> {
+        match self.0 {
+            0 => None,
+            bits => Some(Square::from_u16_panic(bits.trailing_zeros() as u16)),
+        }
+    }
+
+    /// The highest set square, h8 first
+    pub fn msb(&self) -> Option<Square> {
+        match self.0 {
+            0 => None,
+            bits => Some(Square::from_u16_panic(63 - bits.leading_zeros() as u16)),
+        }
+    }
+
+    /// Clears the lowest set square and returns it
+    pub fn pop_lsb(&mut self) -> Option<Square> {
+        let square = self.lsb()?;
+        self.0 &= self.0 - 1;
+        Some(square)
+    }
+
+    /// Whether every square set here is set in `other`
+    pub fn is_subset_of(&self, other: Bitboard) -> bool {
+        self.0 & !other.0 == 0
+    }
+
+    /// Every subset of these squares, the empty set first and this one last, by the
+    /// Carry-Rippler trick
+    ///
+    /// https://www.chessprogramming.org/Traversing_Subsets_of_a_Set
+    pub fn subsets(&self) -> Subsets {
+        Subsets { set: self.0, next: Some(0) }
+    }
+
+    pub fn iter(&self) -> IntoIter {
+        IntoIter(self.0)
+    }
+
+    // Shifts one square in each direction, squares going off the board are lost rather
+    // than wrapping round to the other side
+
+    pub fn north(self) -> Bitboard {
+        Bitboard(self.0 << 8)
+    }
+
+    pub fn south(self) -> Bitboard {
+        Bitboard(self.0 >> 8)
+    }
+
+    pub fn east(self) -> Bitboard {
+        Bitboard((self.0 << 1) & !FILE_A.0)
+    }
+
+    pub fn west(self) -> Bitboard {
+        Bitboard((self.0 >> 1) & !FILE_H.0)
+    }
+
+    pub fn north_east(self) -> Bitboard {
+        Bitboard((self.0 << 9) & !FILE_A.0)
+    }
+
+    pub fn north_west(self) -> Bitboard {
+        Bitboard((self.0 << 7) & !FILE_H.0)
+    }
+
+    pub fn south_east(self) -> Bitboard {
+        Bitboard((self.0 >> 7) & !FILE_A.0)
+    }
+
+    pub fn south_west(self) -> Bitboard {
+        Bitboard((self.0 >> 9) & !FILE_H.0)
+    }
+}
+
+/// The set squares of a `Bitboard`, lowest first
+#[derive(Debug, Clone)]
+pub struct IntoIter(u64);
+
+impl Iterator for IntoIter {
+    type Item = Square;
+
+    fn next(&mut self) -> Option<Square> {
+        let mut bb = Bitboard(self.0);
+        let square = bb.pop_lsb();
+        self.0 = bb.0;
+        square
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for IntoIter {}
+
+impl IntoIterator for Bitboard {
+    type Item = Square;
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> IntoIter {
+        IntoIter(self.0)
+    }
+}
+
+impl FromIterator<Square> for Bitboard {
+    fn from_iter<I: IntoIterator<Item = Square>>(iter: I) -> Self {
+        iter.into_iter().fold(EMPTY, |bb, square| bb | Bitboard::from(square))
+    }
+}
+
+/// Every subset of a `Bitboard`, see `Bitboard::subsets`
+#[derive(Debug, Clone)]
+pub struct Subsets {
+    set: u64,
+    /// `None` once the whole set has been returned
+    next: Option<u64>,
+}
+
+impl Iterator for Subsets {
+    type Item = Bitboard;
+
+    fn next(&mut self) -> Option<Bitboard> {
+        let subset = self.next?;
+        let following = subset.wrapping_sub(self.set) & self.set;
+
+        self.next = (following != 0).then_some(following);
+
+        Some(Bitboard(subset))
+    }
 }
 
 impl From<u64> for Bitboard {
@@ -89,7 +278,7 @@ impl Debug for Bitboard {
 
                 if file != 7 { write!(f, " ")?; }
             }
-            if rank != 0 { write!(f, "\n")?; }
+            if rank != 0 { writeln!(f)?; }
         }
         Ok(())
     }
@@ -113,4 +302,49 @@ impl std::ops::BitXorAssign for Bitboard {
     fn bitxor_assign(&mut self, rhs: Self) {
         self.0 ^= rhs.0;
     }
+}
+
+impl std::ops::BitAnd for Bitboard {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Bitboard(self.0 & rhs.0)
+    }
+}
+
+impl std::ops::BitAndAssign for Bitboard {
+    fn bitand_assign(&mut self, rhs: Self) {
+        self.0 &= rhs.0;
+    }
+}
+
+impl std::ops::BitXor for Bitboard {
+    type Output = Self;
+
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        Bitboard(self.0 ^ rhs.0)
+    }
+}
+
+impl std::ops::Not for Bitboard {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        Bitboard(!self.0)
+    }
+}
+
+/// The squares set here but not in `rhs`
+impl std::ops::Sub for Bitboard {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Bitboard(self.0 & !rhs.0)
+    }
+}
+
+impl std::ops::SubAssign for Bitboard {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.0 &= !rhs.0;
+    }
 }
\ No newline at end of file
@@ -4,7 +4,8 @@ use crate::bitboard;
 use crate::error::ChessError;
 use crate::Bitboard;
 use crate::Square;
-use crate::r#move::Move;
+use crate::r#move::{Move, MoveFlag};
+use crate::zobrist;
 
 pub const FEN_STARTPOS: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
 
@@ -24,6 +25,19 @@ pub const BLACK_PAWN: usize = 11;
 pub const WHITE: usize = 0;
 pub const BLACK: usize = 1;
 
+/// FEN letter for each piece index
+pub const PIECE_CHARS: [char; 12] = ['K', 'Q', 'R', 'B', 'N', 'P', 'k', 'q', 'r', 'b', 'n', 'p'];
+
+/// When `Board::to_fen_with` writes the en passant square
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnPassantMode {
+    /// Whenever the last move was a double pawn push, as the FEN standard says
+    Always,
+    /// Only when an en passant capture is actually legal, as X-FEN and Polyglot expect
+    Legal,
+}
+
+#[derive(Clone)]
 pub struct Board {
     /// 0-5: White pieces
     /// 6-11: Black pieces
@@ -44,6 +58,23 @@ pub struct Board {
     pub fullmove_counter: u32,
 
     pub move_stack: Vec<Move>,
+
+    /// The state `make_move` can't recover from a move alone, one entry for each move
+    /// in `move_stack`
+    pub undo_stack: Vec<Undo>,
+
+    /// Zobrist key, kept up to date by `make_move` and `unmake_move`
+    hash: u64,
+}
+
+/// Everything needed to take back a move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Undo {
+    pub captured: Option<usize>,
+    pub castling_rights: u8,
+    pub en_passant: Option<Square>,
+    pub halfmove_clock: u32,
+    pub hash: u64,
 }
 
 impl Board {
@@ -52,7 +83,21 @@ impl Board {
             .expect("Internal error: Invalid fen: FEN_STARTPOS")
     }
     
+    /// Parses a FEN leniently, accepting any placement of pieces the board can hold, as
+    /// puzzle and study positions sometimes need. See `from_fen_strict` for full validation.
     pub fn from_fen(fen: &str) -> Result<Self, ChessError> {
+        Board::parse_fen(fen, false)
+    }
+
+    /// Parses a FEN, rejecting malformed ranks and any position which couldn't arise in a
+    /// game, see `validate`
+    pub fn from_fen_strict(fen: &str) -> Result<Self, ChessError> {
+        let board = Board::parse_fen(fen, true)?;
+        board.validate()?;
+        Ok(board)
+    }
+
+    fn parse_fen(fen: &str, strict: bool) -> Result<Self, ChessError> {
         let fields: Vec<&str> = fen.split_whitespace().collect();
 
         if fields.len() != 6 {
@@ -81,8 +126,26 @@ impl Board {
         let mut file;
         let mut rank = 7;
 
+        if strict {
+            let ranks = placement.split('/').count();
+
+            if ranks != 8 {
+                return Err(ChessError::InvalidRankCount { ranks });
+            }
+        }
+
         for occupancy in placement.split('/') {
 
+            if strict {
+                let files = occupancy.chars()
+                    .map(|c| c.to_digit(10).map_or(1, |empty| empty as usize))
+                    .sum();
+
+                if files != 8 {
+                    return Err(ChessError::InvalidRankLength { rank: rank + 1, files });
+                }
+            }
+
             file = 0;
             for c in occupancy.chars() {       
                 if file > 7 { break; }
@@ -157,13 +220,20 @@ impl Board {
             _ => Some(Square::from_algebraic(en_passant)?),
         };
 
+        // Move generation finds the captured pawn behind the square, which must be on the board
+        if let Some(square) = en_passant {
+            if !matches!(square.to_u8() / 8, 2 | 5) {
+                return Err(ChessError::InvalidEnPassantRank { square });
+            }
+        }
+
         let halfmove_clock = halfmove_clock.parse::<u32>().map_err(|_| ChessError::InvalidFen {
             msg: format!("Invalid halfmove clock: {}", halfmove_clock) })?;
         
         let fullmove_counter = fullmove_counter.parse::<u32>().map_err(|_| ChessError::InvalidFen {
             msg: format!("Invalid fullmove counter: {}", fullmove_counter) })?;
 
-        Ok(Board {
+        let mut board = Board {
             bitboards,
             side_to_move,
             castling_rights,
@@ -171,21 +241,369 @@ impl Board {
             halfmove_clock,
             fullmove_counter,
             move_stack: Vec::new(),
-        })
+            undo_stack: Vec::new(),
+            hash: 0,
+        };
+
+        board.hash = board.zobrist_key();
+
+        Ok(board)
     }
 
-    pub fn make_move(&mut self, mv: Move) {
-        let from = mv.from_panic();
-        let to = mv.to_panic();
+    /// Zobrist key of the position: the pieces, side to move, castling rights and en
+    /// passant file. The move clocks and history aren't part of it.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    pub fn to_fen(&self) -> String {
+        self.to_fen_with(EnPassantMode::Always)
+    }
+
+    pub fn to_fen_with(&self, ep_mode: EnPassantMode) -> String {
+        let mut fen = String::new();
+
+        for rank in (0..8).rev() {
+            let mut empty = 0;
+
+            for file in 0..8 {
+                match self.piece_on(Square::from_file_rank(file, rank)) {
+                    None => empty += 1,
+                    Some(piece) => {
+                        if empty != 0 {
+                            fen.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        fen.push(PIECE_CHARS[piece]);
+                    },
+                }
+            }
 
-        let toggle_mask = Bitboard::from_square(from) | Bitboard::from_square(to);
+            if empty != 0 {
+                fen.push_str(&empty.to_string());
+            }
 
-        for bb in self.bitboards.iter_mut() {
-            if bb.is_occ(from) {
-                *bb ^= toggle_mask
+            if rank != 0 {
+                fen.push('/');
             }
         }
-        
+
+        fen.push_str(match self.side_to_move {
+            WHITE => " w ",
+            _ => " b ",
+        });
+
+        if self.castling_rights == 0 {
+            fen.push('-');
+        }
+
+        for (i, c) in ['K', 'Q', 'k', 'q'].into_iter().enumerate() {
+            if self.castling_rights & (1 << i) != 0 {
+                fen.push(c);
+            }
+        }
+
+        let en_passant = match ep_mode {
+            EnPassantMode::Always => self.en_passant,
+            EnPassantMode::Legal => self.en_passant.filter(|_| {
+                self.generate_legal_moves()
+                    .iter()
+                    .any(|mv| mv.flag() == MoveFlag::EpCapture)
+            }),
+        };
+
+        match en_passant {
+            Some(sqr) => fen.push_str(&format!(" {} ", sqr)),
+            None => fen.push_str(" - "),
+        }
+
+        fen.push_str(&format!("{} {}", self.halfmove_clock, self.fullmove_counter));
+
+        fen
+    }
+
+    /// Checks that the position could arise in a game of chess, returning the first
+    /// problem found:
+    ///
+    /// - each side has exactly one king
+    /// - no pawns stand on the first or eighth rank
+    /// - each castling right has its king and rook on their home squares
+    /// - the en passant square is on the rank behind a pawn which just double pushed
+    /// - the side not to move isn't in check
+    pub fn validate(&self) -> Result<(), ChessError> {
+        for side in [WHITE, BLACK] {
+            let count = self.bitboards[side * 6 + WHITE_KING].count();
+
+            if count != 1 {
+                return Err(ChessError::InvalidKingCount { side, count });
+            }
+        }
+
+        for pawn in [WHITE_PAWN, BLACK_PAWN] {
+            for file in 0..8 {
+                for rank in [0, 7] {
+                    let square = Square::from_file_rank(file, rank);
+
+                    if self.bitboards[pawn].is_occ(square) {
+                        return Err(ChessError::PawnOnBackRank { square });
+                    }
+                }
+            }
+        }
+
+        for (i, right) in ['K', 'Q', 'k', 'q'].into_iter().enumerate() {
+            if self.castling_rights & (1 << i) == 0 {
+                continue;
+            }
+
+            let side = i / 2;
+            let back_rank = side * 7;
+            let rook_file = if i % 2 == 0 { 7 } else { 0 };
+
+            let king_home = self.bitboards[side * 6 + WHITE_KING]
+                .is_occ(Square::from_file_rank(4, back_rank));
+            let rook_home = self.bitboards[side * 6 + WHITE_ROOK]
+                .is_occ(Square::from_file_rank(rook_file, back_rank));
+
+            if !king_home || !rook_home {
+                return Err(ChessError::InvalidCastlingRight { right });
+            }
+        }
+
+        if let Some(square) = self.en_passant {
+            // The pawn which just moved belongs to the side not to move
+            let (ep_rank, pawn_rank, pawn) = match self.side_to_move {
+                WHITE => (5, 4, BLACK_PAWN),
+                _ => (2, 3, WHITE_PAWN),
+            };
+
+            let file = square.to_u8() as usize % 8;
+
+            if square.to_u8() as usize / 8 != ep_rank {
+                return Err(ChessError::InvalidEnPassantRank { square });
+            }
+
+            if !self.bitboards[pawn].is_occ(Square::from_file_rank(file, pawn_rank)) {
+                return Err(ChessError::EnPassantWithoutPawn { square });
+            }
+        }
+
+        let them = self.side_to_move ^ 1;
+        let their_king = Square::from_u16_panic(
+            u64::from(self.bitboards[them * 6 + WHITE_KING]).trailing_zeros() as u16);
+
+        if self.is_square_attacked(their_king, self.side_to_move) {
+            return Err(ChessError::OpponentInCheck { side: them });
+        }
+
+        Ok(())
+    }
+
+    /// The piece standing on `square`, if any
+    pub fn piece_on(&self, square: Square) -> Option<usize> {
+        self.bitboards.iter().position(|bb| bb.is_occ(square))
+    }
+
+    /// Plays `mv` for the side to move, updating every part of the position from the
+    /// flag bits of the move. The move is assumed to be at least pseudo-legal.
+    pub fn make_move(&mut self, mv: Move) {
+        let from = mv.from();
+        let to = mv.to();
+        let flag = mv.flag();
+
+        let us = self.side_to_move;
+        let them = us ^ 1;
+
+        let piece = self.piece_on(from)
+            .expect("Internal error: make_move from an empty square");
+
+        // Depends on where our pawns are, so taken before any of them move
+        let en_passant_key = self.en_passant_key();
+
+        // The en passant victim sits behind the target square, every other capture on it
+        let captured = match flag {
+            MoveFlag::EpCapture => {
+                let victim = Square::from_u16_panic(match us {
+                    WHITE => to.to_u16() - 8,
+                    _ => to.to_u16() + 8,
+                });
+                self.bitboards[them * 6 + WHITE_PAWN].clear(victim);
+                Some(them * 6 + WHITE_PAWN)
+            },
+            _ => self.piece_on(to),
+        };
+
+        self.undo_stack.push(Undo {
+            captured,
+            castling_rights: self.castling_rights,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            hash: self.hash,
+        });
+
+        let keys = &zobrist::KEYS;
+        let (from_idx, to_idx) = (from.to_u8() as usize, to.to_u8() as usize);
+
+        if let Some(captured) = captured {
+            // The en passant victim is a rank behind the target, to ^ 8 for either side
+            if flag == MoveFlag::EpCapture {
+                self.hash ^= keys.pieces[captured][to_idx ^ 8];
+            } else {
+                self.bitboards[captured].clear(to);
+                self.hash ^= keys.pieces[captured][to_idx];
+            }
+        }
+
+        self.bitboards[piece].clear(from);
+
+        let placed = match flag.promotion_piece() {
+            None => piece,
+            Some(promoted) => us * 6 + promoted,
+        };
+
+        self.bitboards[placed].set(to);
+        self.hash ^= keys.pieces[piece][from_idx] ^ keys.pieces[placed][to_idx];
+
+        if flag.is_castle() {
+            let (rook_from, rook_to) = castling_rook_squares(from, flag);
+            let rook = us * 6 + WHITE_ROOK;
+
+            self.bitboards[rook].clear(rook_from);
+            self.bitboards[rook].set(rook_to);
+            self.hash ^= keys.pieces[rook][rook_from.to_u8() as usize] ^ keys.pieces[rook][rook_to.to_u8() as usize];
+        }
+
+        self.hash ^= keys.castling[self.castling_rights as usize & 15];
+
+        self.castling_rights &= CASTLING_RIGHTS_MASK[from_idx] & CASTLING_RIGHTS_MASK[to_idx];
+
+        self.hash ^= keys.castling[self.castling_rights as usize & 15];
+
+        self.hash ^= en_passant_key;
+
+        self.en_passant = match flag {
+            MoveFlag::DoublePawnPush => Some(Square::from_u16_panic((from.to_u16() + to.to_u16()) / 2)),
+            _ => None,
+        };
+
+        if piece == us * 6 + WHITE_PAWN || captured.is_some() {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+
+        if us == BLACK {
+            self.fullmove_counter += 1;
+        }
+
+        self.side_to_move = them;
+        self.hash ^= keys.black_to_move ^ self.en_passant_key();
+        self.move_stack.push(mv);
+
+        debug_assert_eq!(self.hash, self.zobrist_key(), "Incremental hash out of sync after {}", mv.to_algebraic());
+    }
+
+    /// Takes back the last move made with `make_move`, restoring the previous position
+    /// exactly. Returns the move taken back, or `None` if there were no moves to undo.
+    pub fn unmake_move(&mut self) -> Option<Move> {
+        let mv = self.move_stack.pop()?;
+        let undo = self.undo_stack.pop()
+            .expect("Internal error: undo_stack out of sync with move_stack");
+
+        let from = mv.from();
+        let to = mv.to();
+        let flag = mv.flag();
+
+        let us = self.side_to_move ^ 1;
+
+        let placed = self.piece_on(to)
+            .expect("Internal error: unmake_move to an empty square");
+
+        self.bitboards[placed].clear(to);
+
+        let piece = if flag.is_promotion() { us * 6 + WHITE_PAWN } else { placed };
+
+        self.bitboards[piece].set(from);
+
+        if flag.is_castle() {
+            let (rook_from, rook_to) = castling_rook_squares(from, flag);
+
+            self.bitboards[us * 6 + WHITE_ROOK].clear(rook_to);
+            self.bitboards[us * 6 + WHITE_ROOK].set(rook_from);
+        }
+
+        if let Some(captured) = undo.captured {
+            let square = match flag {
+                MoveFlag::EpCapture => Square::from_u16_panic(match us {
+                    WHITE => to.to_u16() - 8,
+                    _ => to.to_u16() + 8,
+                }),
+                _ => to,
+            };
+
+            self.bitboards[captured].set(square);
+        }
+
+        self.castling_rights = undo.castling_rights;
+        self.en_passant = undo.en_passant;
+        self.halfmove_clock = undo.halfmove_clock;
+        self.hash = undo.hash;
+
+        if us == BLACK {
+            self.fullmove_counter -= 1;
+        }
+
+        self.side_to_move = us;
+
+        Some(mv)
+    }
+}
+
+/// The rook's from and to squares for a castling move by the king on `king_from`
+fn castling_rook_squares(king_from: Square, flag: MoveFlag) -> (Square, Square) {
+    let back_rank = king_from.to_u16() - 4;
+
+    let (rook_from, rook_to) = match flag {
+        MoveFlag::KingCastle => (back_rank + 7, back_rank + 5),
+        _ => (back_rank, back_rank + 3),
+    };
+
+    (Square::from_u16_panic(rook_from), Square::from_u16_panic(rook_to))
+}
+
+/// Castling rights kept when a piece moves from or to each square, clearing the rights
+/// of a king or rook leaving its home square, or a rook being captured on it.
+const CASTLING_RIGHTS_MASK: [u8; 64] = {
+    let mut masks = [0b1111; 64];
+
+    masks[0] = !(1 << 1);
+    masks[4] = !(1 << 0 | 1 << 1);
+    masks[7] = !(1 << 0);
+    masks[56] = !(1 << 3);
+    masks[60] = !(1 << 2 | 1 << 3);
+    masks[63] = !(1 << 2);
+
+    masks
+};
+
+/// Boards are equal when they hold the same position: pieces, side to move, castling
+/// rights and en passant square. Move clocks and history don't count, so a position
+/// reached by different move orders compares and hashes the same.
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash
+            && self.bitboards == other.bitboards
+            && self.side_to_move == other.side_to_move
+            && self.castling_rights == other.castling_rights
+            && self.en_passant == other.en_passant
+    }
+}
+
+impl Eq for Board {}
+
+impl std::hash::Hash for Board {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
     }
 }
 
@@ -266,7 +684,7 @@ impl Display for Board {
                 write!(f, " | Fullmove counter: {}", self.fullmove_counter)?;
             }
 
-            if rank != 0 { write!(f, "\n")?; }
+            if rank != 0 { writeln!(f)?; }
         }
         
         Ok(())
@@ -0,0 +1,193 @@
+use std::fs::File;
+use std::io::{BufReader, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::board::Board;
+use crate::book::{Book, Selection};
+use crate::r#move::Move;
+use crate::search::{search, SearchLimits, SearchResult};
+use crate::tt::TranspositionTable;
+
+/// What the UCI and XBoard front-ends have in common: output shared with a search on a
+/// background thread, the hash table kept between searches and the opening book
+///
+/// `T` is what a search gives back to the front-end when it finishes.
+pub(crate) struct Engine<W: Write + Send + 'static, T: Send + 'static> {
+    out: Arc<Mutex<W>>,
+    /// Locked by the search thread while it runs
+    tt: Arc<Mutex<TranspositionTable>>,
+    search: Option<SearchThread<T>>,
+
+    /// Opening book from the `Book File` option, played from when `OwnBook` is on
+    book: Option<Book<BufReader<File>>>,
+    pub(crate) own_book: bool,
+}
+
+struct SearchThread<T> {
+    /// What the search finished with, `None` if it was abandoned first
+    handle: JoinHandle<Option<T>>,
+    stop: Arc<AtomicBool>,
+    /// Keeps the result back once the search ends, until it's released or stopped
+    hold: Arc<AtomicBool>,
+    /// Set when the result is no longer wanted
+    discard: Arc<AtomicBool>,
+}
+
+impl<W: Write + Send + 'static, T: Send + 'static> Engine<W, T> {
+    pub(crate) fn new(out: W) -> Self {
+        Engine {
+            out: Arc::new(Mutex::new(out)),
+            tt: Arc::new(Mutex::new(TranspositionTable::default())),
+            search: None,
+            book: None,
+            own_book: false,
+        }
+    }
+
+    pub(crate) fn out(&self) -> MutexGuard<'_, W> {
+        lock(&self.out)
+    }
+
+    pub(crate) fn tt(&self) -> MutexGuard<'_, TranspositionTable> {
+        lock(&self.tt)
+    }
+
+    /// Opens the book at `path`, or goes without one when `path` is empty
+    pub(crate) fn set_book(&mut self, path: &str) -> std::io::Result<()> {
+        self.book = None;
+
+        if !path.is_empty() {
+            self.book = Some(Book::open(path)?);
+        }
+
+        Ok(())
+    }
+
+    /// A move from the opening book, if it's in use and has one for `board`
+    pub(crate) fn book_move(&mut self, board: &Board) -> Option<Move> {
+        if !self.own_book {
+            return None;
+        }
+
+        self.book.as_mut()?.pick(board, Selection::WeightedRandom).ok()?
+    }
+
+    /// Searches a copy of `board` on a background thread, giving `report` each iteration
+    /// and `finish` the result, both with the output locked. A held search keeps its
+    /// result back until it's released or stopped.
+    pub(crate) fn start<R, F>(&mut self, board: &Board, limits: SearchLimits, hold: bool, mut report: R, finish: F)
+    where
+        R: FnMut(&mut W, &SearchResult, Duration) + Send + 'static,
+        F: FnOnce(&mut W, Board, SearchResult) -> Option<T> + Send + 'static,
+    {
+        self.abandon();
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let hold = Arc::new(AtomicBool::new(hold));
+        let discard = Arc::new(AtomicBool::new(false));
+
+        let mut board = board.clone();
+        let out = Arc::clone(&self.out);
+        let tt = Arc::clone(&self.tt);
+        let thread_stop = Arc::clone(&stop);
+        let thread_hold = Arc::clone(&hold);
+        let thread_discard = Arc::clone(&discard);
+
+        let handle = std::thread::spawn(move || {
+            let start = Instant::now();
+            let mut tt = lock(&tt);
+
+            let result = search(&mut board, &mut tt, &limits, &thread_stop, |result| {
+                let mut out = lock(&out);
+                report(&mut out, result, start.elapsed());
+                let _ = out.flush();
+            });
+
+            drop(tt);
+
+            while thread_hold.load(Ordering::Relaxed) && !thread_stop.load(Ordering::Relaxed) {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+
+            let mut out = lock(&out);
+
+            // Checked with the output locked so a result is either given in full or not at all
+            if thread_discard.load(Ordering::Relaxed) {
+                return None;
+            }
+
+            let finished = finish(&mut out, board, result);
+            let _ = out.flush();
+
+            finished
+        });
+
+        self.search = Some(SearchThread { handle, stop, hold, discard });
+    }
+
+    /// Whether a search is running, or finished and held
+    pub(crate) fn is_searching(&self) -> bool {
+        self.search.is_some()
+    }
+
+    /// Whether the search keeps its result back until it's released or stopped
+    pub(crate) fn is_held(&self) -> bool {
+        self.search.as_ref().is_some_and(|search| search.hold.load(Ordering::Relaxed))
+    }
+
+    /// Lets a held search give its result when it ends, stopping it after `time`
+    pub(crate) fn release(&self, time: Option<Duration>) {
+        let Some(search) = &self.search else { return; };
+
+        search.hold.store(false, Ordering::Relaxed);
+
+        if let Some(time) = time {
+            let stop = Arc::clone(&search.stop);
+
+            std::thread::spawn(move || {
+                std::thread::sleep(time);
+                stop.store(true, Ordering::Relaxed);
+            });
+        }
+    }
+
+    /// Waits for a search which isn't held to end by itself, returning its result
+    pub(crate) fn wait(&mut self) -> Option<T> {
+        if self.is_held() {
+            return None;
+        }
+
+        self.join()
+    }
+
+    /// Stops the search and waits for its result
+    pub(crate) fn stop(&mut self) -> Option<T> {
+        self.search.as_ref()?.stop.store(true, Ordering::Relaxed);
+        self.join()
+    }
+
+    /// Stops the search without wanting its result, which is only returned if the search
+    /// had already given it
+    pub(crate) fn abandon(&mut self) -> Option<T> {
+        self.search.as_ref()?.discard.store(true, Ordering::Relaxed);
+        self.stop()
+    }
+
+    fn join(&mut self) -> Option<T> {
+        self.search.take()?.handle.join().ok().flatten()
+    }
+}
+
+impl<W: Write + Send + 'static, T: Send + 'static> Drop for Engine<W, T> {
+    fn drop(&mut self) {
+        self.abandon();
+    }
+}
+
+/// Locks `mutex`, carrying on if a thread panicked while holding it
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
@@ -1,5 +1,8 @@
 use std::{fmt, error::Error};
 
+use crate::board::WHITE;
+use crate::square::Square;
+
 #[derive(Debug)]
 pub enum ChessError {
     InvalidFen {
@@ -10,10 +13,67 @@ pub enum ChessError {
     },
     InvalidSquare {
         square: u16,
+    },
+    InvalidMoveFlag {
+        flag: u16,
+    },
+    InvalidRankCount {
+        ranks: usize,
+    },
+    InvalidRankLength {
+        rank: usize,
+        files: usize,
+    },
+    InvalidKingCount {
+        side: usize,
+        count: u32,
+    },
+    PawnOnBackRank {
+        square: Square,
+    },
+    InvalidCastlingRight {
+        right: char,
+    },
+    InvalidEnPassantRank {
+        square: Square,
+    },
+    EnPassantWithoutPawn {
+        square: Square,
+    },
+    OpponentInCheck {
+        side: usize,
+    },
+    InvalidSan {
+        msg: String,
+    },
+    IllegalMove {
+        mv: String,
+    },
+    AmbiguousMove {
+        san: String,
+    },
+    InvalidPgn {
+        line: usize,
+        column: usize,
+        msg: String,
+    },
+    Io(std::io::Error),
+}
+
+impl Error for ChessError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ChessError::Io(e) => Some(e),
+            _ => None,
+        }
     }
 }
 
-impl Error for ChessError {}
+impl From<std::io::Error> for ChessError {
+    fn from(value: std::io::Error) -> Self {
+        ChessError::Io(value)
+    }
+}
 
 impl fmt::Display for ChessError {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
@@ -21,10 +81,38 @@ impl fmt::Display for ChessError {
             ChessError::InvalidFen { msg } => write!(f, "Invalid FEN: {}", msg),
             ChessError::InvalidAlgNotation { msg } => write!(f, "Invalid algebraic notation: {}", msg),
             ChessError::InvalidSquare { square } => write!(f, "Invalid square: {}", square),
+            ChessError::InvalidMoveFlag { flag } => write!(f, "Invalid move flag: {}", flag),
+            ChessError::InvalidRankCount { ranks } => write!(f, "Invalid FEN: {} ranks, expected 8", ranks),
+            ChessError::InvalidRankLength { rank, files } =>
+                write!(f, "Invalid FEN: rank {} has {} files, expected 8", rank, files),
+            ChessError::InvalidKingCount { side, count } =>
+                write!(f, "Illegal position: {} has {} kings", side_name(*side), count),
+            ChessError::PawnOnBackRank { square } => write!(f, "Illegal position: pawn on {}", square),
+            ChessError::InvalidCastlingRight { right } =>
+                write!(f, "Illegal position: castling right {} without king and rook on their home squares", right),
+            ChessError::InvalidEnPassantRank { square } =>
+                write!(f, "Illegal position: en passant square {} on the wrong rank", square),
+            ChessError::EnPassantWithoutPawn { square } =>
+                write!(f, "Illegal position: en passant square {} without a pawn in front of it", square),
+            ChessError::OpponentInCheck { side } =>
+                write!(f, "Illegal position: {} is in check but not to move", side_name(*side)),
+            ChessError::InvalidSan { msg } => write!(f, "Invalid SAN: {}", msg),
+            ChessError::IllegalMove { mv } => write!(f, "Illegal move: {}", mv),
+            ChessError::AmbiguousMove { san } => write!(f, "Ambiguous move: {}", san),
+            ChessError::InvalidPgn { line, column, msg } =>
+                write!(f, "Invalid PGN at line {}, column {}: {}", line, column, msg),
+            ChessError::Io(e) => write!(f, "I/O error: {}", e),
         }
     }
 }
 
+fn side_name(side: usize) -> &'static str {
+    match side {
+        WHITE => "White",
+        _ => "Black",
+    }
+}
+
 impl From<ChessError> for fmt::Error {
     fn from(value: ChessError) -> Self {
         println!("{value}");
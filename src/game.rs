@@ -0,0 +1,296 @@
+use crate::board::Board;
+use crate::error::ChessError;
+use crate::r#move::Move;
+
+/// A node in a `GameTree`, only valid for the tree it came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+/// A position in a `GameTree` and the move that reached it
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Node {
+    parent: Option<NodeId>,
+    /// `None` only for the root
+    mv: Option<Move>,
+    /// The main line first, then the variations in order
+    children: Vec<NodeId>,
+
+    /// Comments before the move, as at the start of a variation
+    pub comments_before: Vec<String>,
+    /// Comments after the move, or on the game as a whole for the root
+    pub comments: Vec<String>,
+    /// Numeric Annotation Glyphs
+    pub nags: Vec<u8>,
+    /// Name and value pairs in the order they were added, eg. `("clk", "0:03:00")` as
+    /// PGN writes `[%clk 0:03:00]`
+    pub annotations: Vec<(String, String)>,
+}
+
+impl Node {
+    pub fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    /// The move played to reach this node, `None` for the root
+    pub fn mv(&self) -> Option<Move> {
+        self.mv
+    }
+
+    /// The moves played from here, the main line first
+    pub fn children(&self) -> &[NodeId] {
+        &self.children
+    }
+
+    /// The value of the first annotation called `name`
+    pub fn annotation(&self, name: &str) -> Option<&str> {
+        self.annotations.iter()
+            .find(|(annotation, _)| annotation == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Sets the annotation called `name`, replacing any it had
+    pub fn set_annotation(&mut self, name: &str, value: &str) {
+        match self.annotations.iter_mut().find(|(annotation, _)| annotation == name) {
+            Some((_, old)) => *old = value.to_string(),
+            None => self.annotations.push((name.to_string(), value.to_string())),
+        }
+    }
+}
+
+/// A game with all its variations: a starting position and a tree of moves from it
+///
+/// Each node's first child continues the main line and the rest are variations on it.
+/// Nodes are addressed by `NodeId` and there's a cursor, `current`, for stepping through
+/// the game as a GUI would. Positions aren't stored, `board_at` replays the moves from
+/// the root.
+#[derive(Clone)]
+pub struct GameTree {
+    root_board: Board,
+    /// Indexed by `NodeId`, deleted variations are unlinked and left here unreachable
+    nodes: Vec<Node>,
+    current: NodeId,
+}
+
+impl Default for GameTree {
+    fn default() -> Self {
+        Self::new(Board::startpos())
+    }
+}
+
+impl GameTree {
+    /// A tree with no moves, starting from `board`
+    pub fn new(board: Board) -> Self {
+        GameTree {
+            root_board: board,
+            nodes: vec![Node::default()],
+            current: NodeId(0),
+        }
+    }
+
+    pub fn root(&self) -> NodeId {
+        NodeId(0)
+    }
+
+    /// The starting position
+    pub fn root_board(&self) -> &Board {
+        &self.root_board
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id.0]
+    }
+
+    pub fn node_mut(&mut self, id: NodeId) -> &mut Node {
+        &mut self.nodes[id.0]
+    }
+
+    /// The position after the moves leading to `id`
+    pub fn board_at(&self, id: NodeId) -> Board {
+        let mut board = self.root_board.clone();
+
+        for mv in self.moves_to(id) {
+            board.make_move(mv);
+        }
+
+        board
+    }
+
+    /// The moves from the root leading to `id`
+    pub fn moves_to(&self, id: NodeId) -> Vec<Move> {
+        let mut moves = Vec::new();
+        let mut node = Some(id);
+
+        while let Some(id) = node {
+            moves.extend(self.node(id).mv);
+            node = self.node(id).parent;
+        }
+
+        moves.reverse();
+        moves
+    }
+
+    /// The main line from `id` on, not including `id` itself
+    pub fn main_line(&self, id: NodeId) -> Vec<NodeId> {
+        let mut line = Vec::new();
+        let mut node = id;
+
+        while let Some(&next) = self.node(node).children.first() {
+            line.push(next);
+            node = next;
+        }
+
+        line
+    }
+
+    /// Adds `mv` after `parent` as its last variation, or its main line when it has no
+    /// moves yet. A move that's already there isn't added twice, its node is returned.
+    pub fn add_move(&mut self, parent: NodeId, mv: Move) -> Result<NodeId, ChessError> {
+        if let Some(&existing) = self.node(parent).children.iter().find(|&&child| self.node(child).mv == Some(mv)) {
+            return Ok(existing);
+        }
+
+        if !self.board_at(parent).generate_legal_moves().contains(&mv) {
+            return Err(ChessError::IllegalMove { mv: mv.to_algebraic() });
+        }
+
+        let id = NodeId(self.nodes.len());
+
+        self.nodes.push(Node { parent: Some(parent), mv: Some(mv), ..Node::default() });
+        self.nodes[parent.0].children.push(id);
+
+        Ok(id)
+    }
+
+    /// Adds a move in SAN after `parent`, see `add_move`
+    pub fn add_san(&mut self, parent: NodeId, san: &str) -> Result<NodeId, ChessError> {
+        let mv = self.board_at(parent).parse_san(san)?;
+
+        self.add_move(parent, mv)
+    }
+
+    /// The node the cursor is on
+    pub fn current(&self) -> NodeId {
+        self.current
+    }
+
+    /// The position at the cursor
+    pub fn board(&self) -> Board {
+        self.board_at(self.current)
+    }
+
+    pub fn go_to(&mut self, id: NodeId) {
+        self.current = id;
+    }
+
+    pub fn go_to_root(&mut self) {
+        self.current = self.root();
+    }
+
+    /// Plays `mv` at the cursor, as a new variation if it's not already there, and moves
+    /// on to it
+    pub fn play(&mut self, mv: Move) -> Result<NodeId, ChessError> {
+        self.current = self.add_move(self.current, mv)?;
+
+        Ok(self.current)
+    }
+
+    /// Steps along the main line, returning false at the end of it
+    pub fn forward(&mut self) -> bool {
+        self.enter_variation(0)
+    }
+
+    /// Steps back a move, returning false at the root
+    pub fn back(&mut self) -> bool {
+        match self.node(self.current).parent {
+            Some(parent) => {
+                self.current = parent;
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Steps into the `index`th move from the cursor, 0 being the main line, returning
+    /// false when there isn't one
+    pub fn enter_variation(&mut self, index: usize) -> bool {
+        match self.node(self.current).children.get(index) {
+            Some(&child) => {
+                self.current = child;
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Moves the variation starting at `id` one place up among its siblings, so the
+    /// first variation becomes the main line. Returns false when it's already first.
+    pub fn promote_variation(&mut self, id: NodeId) -> bool {
+        self.move_variation(id, |index| index.checked_sub(1))
+    }
+
+    /// Moves the variation starting at `id` one place down among its siblings. Returns
+    /// false when it's already last.
+    pub fn demote_variation(&mut self, id: NodeId) -> bool {
+        self.move_variation(id, |index| Some(index + 1))
+    }
+
+    /// Makes the line through `id` the main line all the way from the root
+    pub fn promote_to_main_line(&mut self, id: NodeId) {
+        let mut node = id;
+
+        while let Some(parent) = self.node(node).parent {
+            let children = &mut self.nodes[parent.0].children;
+
+            if let Some(index) = children.iter().position(|&child| child == node) {
+                let child = children.remove(index);
+                children.insert(0, child);
+            }
+
+            node = parent;
+        }
+    }
+
+    /// Removes `id` and every move after it. The cursor moves back to its parent if it
+    /// was in there. Returns false for the root, which can't be deleted.
+    pub fn delete_variation(&mut self, id: NodeId) -> bool {
+        let Some(parent) = self.node(id).parent else { return false; };
+
+        self.nodes[parent.0].children.retain(|&child| child != id);
+
+        if self.is_within(self.current, id) {
+            self.current = parent;
+        }
+
+        true
+    }
+
+    /// Whether `id` is `ancestor` or comes after it
+    fn is_within(&self, id: NodeId, ancestor: NodeId) -> bool {
+        let mut node = Some(id);
+
+        while let Some(id) = node {
+            if id == ancestor {
+                return true;
+            }
+
+            node = self.node(id).parent;
+        }
+
+        false
+    }
+
+    fn move_variation(&mut self, id: NodeId, to: impl Fn(usize) -> Option<usize>) -> bool {
+        let Some(parent) = self.node(id).parent else { return false; };
+        let children = &mut self.nodes[parent.0].children;
+
+        let Some(index) = children.iter().position(|&child| child == id) else { return false; };
+
+        match to(index).filter(|&new| new < children.len()) {
+            Some(new) => {
+                children.swap(index, new);
+                true
+            },
+            None => false,
+        }
+    }
+}
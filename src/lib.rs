@@ -1,9 +1,25 @@
+pub mod attacks;
+pub mod bitboard;
 pub mod board;
+pub mod book;
 pub mod error;
+pub mod eval;
+pub mod game;
+pub mod movegen;
 pub mod r#move;
+pub mod notation;
+pub mod outcome;
+pub mod perft;
+pub mod pgn;
+pub mod search;
+pub mod see;
+pub mod square;
+pub mod tt;
+pub mod uci;
+pub mod xboard;
 
-mod bitboard;
-mod square;
+mod engine;
+mod zobrist;
 
 use bitboard::Bitboard;
 use square::Square;
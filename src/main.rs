@@ -1,32 +1,84 @@
-use std::io::{BufRead, Write};
+use std::io::{BufRead, IsTerminal, Write};
+
+use std::sync::atomic::AtomicBool;
 
 use jesschess::board::Board;
+use jesschess::book::BookBuilder;
+use jesschess::search::{search, SearchLimits};
+use jesschess::tt::TranspositionTable;
+use jesschess::uci::Uci;
+use jesschess::xboard::XBoard;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    println!("jesschess version: {}", jesschess::version());
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    if let Some("book") = args.first().map(String::as_str) {
+        if let Err(e) = build_book(&args[1..]) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+
+        return Ok(());
+    }
+
+    // GUIs talk over pipes and expect nothing before their handshake, so the banner and
+    // prompt are only for someone at a terminal
+    let interactive = std::io::stdin().is_terminal();
+
+    if interactive {
+        println!("jesschess version: {}", jesschess::version());
+    }
 
     let mut stdin = std::io::stdin().lock();
     let mut cmd_buf = String::new();
     let mut board = None;
+    let mut tt = TranspositionTable::default();
+    let mut first = true;
 
     loop {
-        print!("jesschess> ");
-        std::io::stdout().flush()?;
+        if interactive {
+            print!("jesschess> ");
+            std::io::stdout().flush()?;
+        }
 
         cmd_buf.clear();
         
-        stdin.read_line(&mut cmd_buf)?;
+        // End of input, as when commands are piped in
+        if stdin.read_line(&mut cmd_buf)? == 0 {
+            break;
+        }
         let cmd = cmd_buf
             .split_whitespace()
             .collect::<Vec<_>>();
 
+        // GUIs open with "uci" or "xboard", hand the rest of the session over to the protocol
+        if first && cmd == ["uci"] {
+            let mut uci = Uci::new(std::io::stdout());
+
+            uci.handle(&cmd_buf)?;
+            uci.run(stdin)?;
+
+            return Ok(());
+        }
+
+        if first && cmd == ["xboard"] {
+            let mut xboard = XBoard::new(std::io::stdout());
+
+            xboard.handle(&cmd_buf)?;
+            xboard.run(stdin)?;
+
+            return Ok(());
+        }
+
+        first = false;
+
         match cmd.as_slice() {
             ["quit" | "q"] => {
                 println!("bye!");
                 break
             },
             ["version"] => println!("{}", jesschess::version()),
-            ["help" | "h"] => println!("Commands: quit/q, version, help/h, position/pos, print/p"),
+            ["help" | "h"] => println!("Commands: quit/q, version, help/h, position/pos, print/p, perft, go, uci, xboard. Run with `book` to make an opening book"),
             ["position" | "pos", "startpos"] => {
                 board = Some(Board::startpos());
             },
@@ -43,9 +95,95 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     None => println!("No board loaded"),
                 }
             }
+            ["go", depth] => {
+                let Some(b) = &mut board else {
+                    println!("No board loaded");
+                    continue;
+                };
+
+                let Ok(depth) = depth.parse::<u32>() else {
+                    println!("Invalid depth: {}", depth);
+                    continue;
+                };
+
+                let limits = SearchLimits { depth: Some(depth), ..Default::default() };
+                let stop = AtomicBool::new(false);
+
+                let result = search(b, &mut tt, &limits, &stop, |result| {
+                    let pv = result.pv.iter().map(|mv| mv.to_algebraic()).collect::<Vec<_>>();
+                    println!("depth {} score {} nodes {} pv {}", result.depth, result.score, result.nodes, pv.join(" "));
+                });
+
+                match result.best_move {
+                    Some(mv) => println!("Best move: {}", mv.to_algebraic()),
+                    None => println!("No legal moves"),
+                }
+            }
+            ["perft", depth] => {
+                let Some(b) = &mut board else {
+                    println!("No board loaded");
+                    continue;
+                };
+
+                let Ok(depth) = depth.parse::<u32>() else {
+                    println!("Invalid depth: {}", depth);
+                    continue;
+                };
+
+                let start = std::time::Instant::now();
+                let nodes = b.divide(depth, &mut std::io::stdout())?;
+                let elapsed = start.elapsed();
+
+                println!();
+                println!("Nodes: {}", nodes);
+                println!("Time: {}ms ({:.0} nps)", elapsed.as_millis(), nodes as f64 / elapsed.as_secs_f64());
+            }
             _ => println!("Unknown command: {}", cmd.join(" ")),
         }
     }
     
     Ok(())
-}
\ No newline at end of file
+}
+
+const BOOK_USAGE: &str = "Usage: jesschess book [--min-games N] [--max-ply N] [--player NAME] [--min-elo N] -o <book.bin> <games.pgn>...";
+
+/// `jesschess book`, makes a Polyglot book from PGN files
+fn build_book(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = BookBuilder::new();
+    let mut output = None;
+    let mut inputs = Vec::new();
+
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("Missing value for {}\n{}", arg, BOOK_USAGE));
+
+        match arg.as_str() {
+            "-o" | "--output" => output = Some(value()?.clone()),
+            "--min-games" => builder.min_games = value()?.parse()?,
+            "--max-ply" => builder.max_ply = Some(value()?.parse()?),
+            "--player" => builder.player = Some(value()?.clone()),
+            "--min-elo" => builder.min_elo = Some(value()?.parse()?),
+            flag if flag.starts_with('-') => return Err(format!("Unknown option {}\n{}", flag, BOOK_USAGE).into()),
+            input => inputs.push(input.to_string()),
+        }
+    }
+
+    let Some(output) = output.filter(|_| !inputs.is_empty()) else {
+        return Err(BOOK_USAGE.into());
+    };
+
+    for input in &inputs {
+        let games = builder.add_pgn(std::io::BufReader::new(std::fs::File::open(input)?))?;
+        println!("{}: {} games", input, games);
+    }
+
+    let entries = builder.entries();
+
+    let mut out = std::io::BufWriter::new(std::fs::File::create(&output)?);
+    builder.write(&mut out)?;
+
+    println!("{}: {} entries from {} games", output, entries.len(), builder.games());
+
+    Ok(())
+}
@@ -1,20 +1,26 @@
 use std::fmt::Display;
 
 use crate::{square::Square, error::ChessError};
+use crate::board::{WHITE_QUEEN, WHITE_ROOK, WHITE_BISHOP, WHITE_KNIGHT};
 
 
 /// Move
-/// 
+///
 /// Encoding:
-/// 
-/// 8 bits | 0-5:   from
-/// 
-/// 8 bits | 6-11:  to
-/// 
-/// 4 bits | 12-15: promotion
+///
+/// 6 bits | 0-5:   from
+///
+/// 6 bits | 6-11:  to
+///
+/// 4 bits | 12-15: flags, see [`MoveFlag`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Move(u16);
+
+/// The kind of move, stored in the top 4 bits of a [`Move`]
 ///
 /// https://www.chessprogramming.org/Encoding_Moves#From-To_Based
 ///
+/// ```text
 /// code | promotion | capture | special 1 | special 0 | kind of move
 ///  0      0           0         0           0           quiet moves
 ///  1      0           0         0           1           double pawn push
@@ -29,51 +35,144 @@ use crate::{square::Square, error::ChessError};
 ///  12     1           1         0           0           knight-promo capture
 ///  13     1           1         0           1           bishop-promo capture
 ///  14     1           1         1           0           rook-promo capture
-///  15     1           1         1           1           queen-promo capture 
-pub struct Move(u16);
+///  15     1           1         1           1           queen-promo capture
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u16)]
+pub enum MoveFlag {
+    Quiet = 0,
+    DoublePawnPush = 1,
+    KingCastle = 2,
+    QueenCastle = 3,
+    Capture = 4,
+    EpCapture = 5,
+    KnightPromotion = 8,
+    BishopPromotion = 9,
+    RookPromotion = 10,
+    QueenPromotion = 11,
+    KnightPromotionCapture = 12,
+    BishopPromotionCapture = 13,
+    RookPromotionCapture = 14,
+    QueenPromotionCapture = 15,
+}
 
-impl Move {
-    pub fn from_raw(raw: u16) -> Move {
-        Move(raw)
+const PROMOTION_BIT: u16 = 1 << 3;
+const CAPTURE_BIT: u16 = 1 << 2;
+
+impl MoveFlag {
+    pub fn from_u16(n: u16) -> Result<Self, ChessError> {
+        use MoveFlag::*;
+
+        match n {
+            0 => Ok(Quiet), 1 => Ok(DoublePawnPush), 2 => Ok(KingCastle), 3 => Ok(QueenCastle),
+            4 => Ok(Capture), 5 => Ok(EpCapture),
+            8 => Ok(KnightPromotion), 9 => Ok(BishopPromotion),
+            10 => Ok(RookPromotion), 11 => Ok(QueenPromotion),
+            12 => Ok(KnightPromotionCapture), 13 => Ok(BishopPromotionCapture),
+            14 => Ok(RookPromotionCapture), 15 => Ok(QueenPromotionCapture),
+            _ => Err(ChessError::InvalidMoveFlag { flag: n }),
+        }
     }
 
-    pub fn new(from: Square, to: Square, promotion: u16) -> Move {
-        let mut mv: u16 = from.to_u16() << 6| to.to_u16() & 0b0000_111111_111111;
-        mv |= promotion << 12;
-        Move(mv)
+    pub fn to_u16(self) -> u16 {
+        self as u16
     }
 
-    pub fn to_u16(&self) -> u16 {
-        self.0
+    /// The promotion flag for promoting to `piece` (a white piece index), with or without
+    /// a capture
+    pub fn promotion(piece: usize, capture: bool) -> Option<Self> {
+        let code = match piece {
+            WHITE_KNIGHT => 0,
+            WHITE_BISHOP => 1,
+            WHITE_ROOK => 2,
+            WHITE_QUEEN => 3,
+            _ => return None,
+        };
+
+        let capture = if capture { CAPTURE_BIT } else { 0 };
+
+        MoveFlag::from_u16(PROMOTION_BIT | capture | code).ok()
+    }
+
+    pub fn is_capture(self) -> bool {
+        self.to_u16() & CAPTURE_BIT != 0
     }
 
-    pub fn from(&self) -> Result<Square, ChessError> {
-        Square::from_u16(self.0 & 0b0000_000000_111111)
+    pub fn is_promotion(self) -> bool {
+        self.to_u16() & PROMOTION_BIT != 0
     }
 
-    pub fn to(&self) -> Result<Square, ChessError> {
-        Square::from_u16((self.0 & 0b0000_111111_000000) >> 6)
+    pub fn is_castle(self) -> bool {
+        matches!(self, MoveFlag::KingCastle | MoveFlag::QueenCastle)
     }
 
-    pub fn from_panic(&self) -> Square {
+    /// The piece promoted to as a white piece index, eg. [`WHITE_QUEEN`]
+    pub fn promotion_piece(self) -> Option<usize> {
+        if !self.is_promotion() {
+            return None;
+        }
+
+        Some(match self.to_u16() & 0b11 {
+            0 => WHITE_KNIGHT,
+            1 => WHITE_BISHOP,
+            2 => WHITE_ROOK,
+            _ => WHITE_QUEEN,
+        })
+    }
+}
+
+impl Move {
+    /// Builds a move from its raw encoding, rejecting the unused flag codes 6 and 7
+    pub fn from_raw(raw: u16) -> Result<Move, ChessError> {
+        MoveFlag::from_u16(raw >> 12)?;
+        Ok(Move(raw))
+    }
+
+    pub fn new(from: Square, to: Square, flag: MoveFlag) -> Move {
+        Move(from.to_u16() | to.to_u16() << 6 | flag.to_u16() << 12)
+    }
+
+    pub fn to_u16(self) -> u16 {
+        self.0
+    }
+
+    pub fn from(self) -> Square {
         Square::from_u16_panic(self.0 & 0b0000_000000_111111)
     }
 
-    pub fn to_panic(&self) -> Square {
+    pub fn to(self) -> Square {
         Square::from_u16_panic((self.0 & 0b0000_111111_000000) >> 6)
     }
 
-    pub fn promotion(&self) -> u16 {
-        (self.0 & 0b1111_000000_000000) >> 12
+    pub fn flag(self) -> MoveFlag {
+        MoveFlag::from_u16(self.0 >> 12)
+            .expect("Internal error: Move with an invalid flag")
+    }
+
+    pub fn is_capture(self) -> bool {
+        self.flag().is_capture()
+    }
+
+    pub fn is_promotion(self) -> bool {
+        self.flag().is_promotion()
+    }
+
+    pub fn is_castle(self) -> bool {
+        self.flag().is_castle()
+    }
+
+    /// The piece promoted to as a white piece index, eg. [`WHITE_QUEEN`]
+    pub fn promotion_piece(self) -> Option<usize> {
+        self.flag().promotion_piece()
     }
 
     /// Parses out a move from algebraic notation, eg. e2e4, e7e8q, e1g1, e7e5
-    /// 
+    ///
     /// Without context from a game, whilst we know about any promoted pieces, we
     /// dont know some metadata about the move such as if it was a capture or not,
-    /// so we just set promotion bits.
+    /// so only the promotion flags are set, everything else is a quiet move.
     pub fn from_algebraic_simple(algr: &str) -> Result<Move, ChessError> {
-        if algr.len() < 4 {
+        if algr.len() < 4 || !algr.is_char_boundary(4) {
             return Err(ChessError::InvalidAlgNotation {
                 msg: format!("Invalid algebraic move notation: {}", algr) });
         }
@@ -81,49 +180,38 @@ impl Move {
         let from = algr[0..2].parse::<Square>()?;
         let to = algr[2..4].parse::<Square>()?;
 
-        let mut mv: u16 = to.to_u16() << 6| from.to_u16() & 0b0000_111111_111111;
-
-        let promotion = match algr.get(4..5) {
-            None => 0,
-            Some("n") => 1 << 3 | 0,
-            Some("b") => 1 << 3 | 1 << 0,
-            Some("r") => 1 << 3 | 1 << 1,
-            Some("q") => 1 << 3 | 1 << 0 | 1 << 1,
-            Some(p) => return Err(ChessError::InvalidAlgNotation {
+        let flag = match &algr[4..] {
+            "" => MoveFlag::Quiet,
+            "n" => MoveFlag::KnightPromotion,
+            "b" => MoveFlag::BishopPromotion,
+            "r" => MoveFlag::RookPromotion,
+            "q" => MoveFlag::QueenPromotion,
+            p => return Err(ChessError::InvalidAlgNotation {
                 msg: format!("Invalid promo piece ({}): {}", algr, p) })
         };
 
-        mv |= promotion << 12;
-
-        Ok(Move(mv))
+        Ok(Move::new(from, to, flag))
     }
 
-    pub fn to_algebraic(&self) -> Result<String, ChessError> {
-        let from = self.from()?;
-        let to = self.to()?;
-
-        let mut algr = format!("{}{}", from, to);
-
-        let promotion = self.promotion();
+    pub fn to_algebraic(self) -> String {
+        let mut algr = format!("{}{}", self.from(), self.to());
 
-        if promotion & 1 << 3 != 0 {
-            algr.push_str(match promotion & 0b11 {
-                0 => "n",
-                1 => "b",
-                2 => "r",
-                3 => "q",
-                _ => return Err(ChessError::InvalidAlgNotation {
-                    msg: format!("Invalid promotion piece: {}", promotion) })
+        if let Some(piece) = self.promotion_piece() {
+            algr.push(match piece {
+                WHITE_KNIGHT => 'n',
+                WHITE_BISHOP => 'b',
+                WHITE_ROOK => 'r',
+                _ => 'q',
             });
         }
 
-        Ok(algr)
+        algr
     }
 
 }
 
 impl Display for Move {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} -> {}", self.from()?, self.to()?)
+        write!(f, "{} -> {}", self.from(), self.to())
     }
 }
@@ -0,0 +1,345 @@
+use crate::attacks::{KING_ATTACKS, KNIGHT_ATTACKS, PAWN_ATTACKS, SLIDERS};
+use crate::board::*;
+use crate::r#move::{Move, MoveFlag};
+use crate::{Bitboard, Square};
+
+const RANK_1: u64 = 0xff;
+const RANK_8: u64 = RANK_1 << 56;
+
+/// The indices of the set bits, lowest first
+fn squares(bb: u64) -> impl Iterator<Item = u8> {
+    Bitboard::from(bb).into_iter().map(Square::to_u8)
+}
+
+fn encode(from: u8, to: u8, flag: MoveFlag) -> Move {
+    Move::new(Square::from_u16_panic(from as u16), Square::from_u16_panic(to as u16), flag)
+}
+
+pub(crate) fn knight_attacks(sq: u8) -> u64 {
+    KNIGHT_ATTACKS[sq as usize]
+}
+
+pub(crate) fn king_attacks(sq: u8) -> u64 {
+    KING_ATTACKS[sq as usize]
+}
+
+pub(crate) fn pawn_attacks(side: usize, sq: u8) -> u64 {
+    PAWN_ATTACKS[side][sq as usize]
+}
+
+pub(crate) fn rook_attacks(sq: u8, occupancy: u64) -> u64 {
+    SLIDERS.rook(sq, occupancy)
+}
+
+pub(crate) fn bishop_attacks(sq: u8, occupancy: u64) -> u64 {
+    SLIDERS.bishop(sq, occupancy)
+}
+
+pub(crate) fn queen_attacks(sq: u8, occupancy: u64) -> u64 {
+    rook_attacks(sq, occupancy) | bishop_attacks(sq, occupancy)
+}
+
+/// The squares strictly between `a` and `b` when they share a rank, file or diagonal,
+/// otherwise empty
+fn between(a: u8, b: u8) -> u64 {
+    let (a_bb, b_bb) = (1u64 << a, 1u64 << b);
+
+    if rook_attacks(a, 0) & b_bb != 0 {
+        return rook_attacks(a, b_bb) & rook_attacks(b, a_bb);
+    }
+
+    if bishop_attacks(a, 0) & b_bb != 0 {
+        return bishop_attacks(a, b_bb) & bishop_attacks(b, a_bb);
+    }
+
+    0
+}
+
+impl Board {
+    /// Every piece belonging to `side`
+    pub(crate) fn side_occupancy(&self, side: usize) -> u64 {
+        self.bitboards[side * 6..side * 6 + 6]
+            .iter()
+            .fold(0, |occ, &bb| occ | u64::from(bb))
+    }
+
+    pub(crate) fn piece_bb(&self, piece: usize) -> u64 {
+        u64::from(self.bitboards[piece])
+    }
+
+    /// Every piece of either side attacking `sq`, given the `occupancy` blocking sliders
+    pub(crate) fn attackers_to(&self, sq: u8, occupancy: u64) -> u64 {
+        let rooks = self.piece_bb(WHITE_ROOK) | self.piece_bb(BLACK_ROOK)
+            | self.piece_bb(WHITE_QUEEN) | self.piece_bb(BLACK_QUEEN);
+        let bishops = self.piece_bb(WHITE_BISHOP) | self.piece_bb(BLACK_BISHOP)
+            | self.piece_bb(WHITE_QUEEN) | self.piece_bb(BLACK_QUEEN);
+        let knights = self.piece_bb(WHITE_KNIGHT) | self.piece_bb(BLACK_KNIGHT);
+        let kings = self.piece_bb(WHITE_KING) | self.piece_bb(BLACK_KING);
+
+        pawn_attacks(BLACK, sq) & self.piece_bb(WHITE_PAWN)
+            | pawn_attacks(WHITE, sq) & self.piece_bb(BLACK_PAWN)
+            | knight_attacks(sq) & knights
+            | king_attacks(sq) & kings
+            | rook_attacks(sq, occupancy) & rooks
+            | bishop_attacks(sq, occupancy) & bishops
+    }
+
+    /// Is `square` attacked by any piece belonging to `side`
+    pub fn is_square_attacked(&self, square: Square, side: usize) -> bool {
+        let occupancy = self.side_occupancy(WHITE) | self.side_occupancy(BLACK);
+
+        self.attackers_to(square.to_u8(), occupancy) & self.side_occupancy(side) != 0
+    }
+
+    /// The enemy pieces giving check to the side to move
+    pub(crate) fn checkers(&self) -> u64 {
+        let us = self.side_to_move;
+        let king = self.piece_bb(us * 6 + WHITE_KING);
+
+        if king == 0 { return 0; }
+
+        let occupancy = self.side_occupancy(WHITE) | self.side_occupancy(BLACK);
+
+        self.attackers_to(king.trailing_zeros() as u8, occupancy) & self.side_occupancy(us ^ 1)
+    }
+
+    /// Is the side to move in check
+    pub fn in_check(&self) -> bool {
+        self.checkers() != 0
+    }
+
+    /// Generates every legal move for the side to move.
+    ///
+    /// Checkers, pinned pieces and the check evasion mask are worked out up front, then
+    /// used to filter the pseudo-legal moves, so no move is ever made on the board.
+    pub fn generate_legal_moves(&self) -> Vec<Move> {
+        let mut moves = self.generate_pseudo_legal_moves();
+
+        let us = self.side_to_move;
+        let king_bb = self.piece_bb(us * 6 + WHITE_KING);
+
+        // Without a king there is nothing to leave in check
+        if king_bb == 0 { return moves; }
+
+        let king = king_bb.trailing_zeros() as u8;
+        let own = self.side_occupancy(us);
+        let enemy = self.side_occupancy(us ^ 1);
+        let occupancy = own | enemy;
+
+        let checkers = self.checkers();
+
+        // A single check can be evaded by capturing the checker or blocking the line
+        // between it and the king. Double checks leave only king moves.
+        let evasion_mask = match checkers.count_ones() {
+            0 => !0,
+            1 => checkers | between(king, checkers.trailing_zeros() as u8),
+            _ => 0,
+        };
+
+        // Each pinned piece may only move along the line between the king and its pinner
+        let mut pin_masks = [!0u64; 64];
+
+        let them = (us ^ 1) * 6;
+        let enemy_rooks = self.piece_bb(them + WHITE_ROOK) | self.piece_bb(them + WHITE_QUEEN);
+        let enemy_bishops = self.piece_bb(them + WHITE_BISHOP) | self.piece_bb(them + WHITE_QUEEN);
+
+        let snipers = rook_attacks(king, enemy) & enemy_rooks
+            | bishop_attacks(king, enemy) & enemy_bishops;
+
+        for sniper in squares(snipers) {
+            let line = between(king, sniper);
+            let blockers = line & occupancy;
+
+            if blockers.count_ones() == 1 && blockers & own != 0 {
+                pin_masks[blockers.trailing_zeros() as usize] = line | 1 << sniper;
+            }
+        }
+
+        // The king can't step along a checking slider's line, so look through it
+        let occupancy_without_king = occupancy & !king_bb;
+
+        moves.retain(|mv| {
+            let from = mv.from().to_u8();
+            let to = mv.to().to_u8();
+            let flag = mv.flag();
+
+            if flag.is_castle() {
+                return checkers == 0 && self.castling_path_safe(king, to, occupancy);
+            }
+
+            if from == king {
+                return self.attackers_to(to, occupancy_without_king) & enemy == 0;
+            }
+
+            if flag == MoveFlag::EpCapture {
+                return self.en_passant_legal(king, from, to, occupancy);
+            }
+
+            evasion_mask & 1 << to != 0 && pin_masks[from as usize] & 1 << to != 0
+        });
+
+        moves
+    }
+
+    /// Neither the square the king passes through nor the one it lands on may be attacked
+    fn castling_path_safe(&self, king: u8, to: u8, occupancy: u64) -> bool {
+        let enemy = self.side_occupancy(self.side_to_move ^ 1);
+        let passed = (king + to) / 2;
+
+        self.attackers_to(passed, occupancy) & enemy == 0
+            && self.attackers_to(to, occupancy) & enemy == 0
+    }
+
+    /// En passant removes two pieces from the same rank at once, which can expose the king
+    /// to a slider along that rank even when neither pawn is pinned on its own, so the
+    /// resulting position is checked directly.
+    fn en_passant_legal(&self, king: u8, from: u8, to: u8, occupancy: u64) -> bool {
+        let victim = match self.side_to_move {
+            WHITE => to - 8,
+            _ => to + 8,
+        };
+
+        let occupancy = occupancy & !(1 << from | 1 << victim) | 1 << to;
+        let enemy = self.side_occupancy(self.side_to_move ^ 1) & !(1 << victim);
+
+        self.attackers_to(king, occupancy) & enemy == 0
+    }
+
+    /// Generates every pseudo-legal move for the side to move, that is every move
+    /// obeying the piece movement rules without regard for leaving the king in check.
+    pub fn generate_pseudo_legal_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::with_capacity(64);
+
+        let us = self.side_to_move;
+        let own = self.side_occupancy(us);
+        let enemy = self.side_occupancy(us ^ 1);
+        let occupancy = own | enemy;
+
+        self.generate_pawn_moves(&mut moves, enemy, occupancy);
+
+        let offset = us * 6;
+
+        for from in squares(self.piece_bb(offset + WHITE_KNIGHT)) {
+            push_moves(&mut moves, from, knight_attacks(from) & !own, enemy);
+        }
+
+        for from in squares(self.piece_bb(offset + WHITE_BISHOP)) {
+            push_moves(&mut moves, from, bishop_attacks(from, occupancy) & !own, enemy);
+        }
+
+        for from in squares(self.piece_bb(offset + WHITE_ROOK)) {
+            push_moves(&mut moves, from, rook_attacks(from, occupancy) & !own, enemy);
+        }
+
+        for from in squares(self.piece_bb(offset + WHITE_QUEEN)) {
+            push_moves(&mut moves, from, queen_attacks(from, occupancy) & !own, enemy);
+        }
+
+        for from in squares(self.piece_bb(offset + WHITE_KING)) {
+            push_moves(&mut moves, from, king_attacks(from) & !own, enemy);
+        }
+
+        self.generate_castling_moves(&mut moves, occupancy);
+
+        moves
+    }
+
+    fn generate_pawn_moves(&self, moves: &mut Vec<Move>, enemy: u64, occupancy: u64) {
+        let us = self.side_to_move;
+        let pawns = self.piece_bb(us * 6 + WHITE_PAWN);
+        let empty = !occupancy;
+
+        // Pushes are computed set-wise, then walked back to their origin square
+        let (single, double, forward): (u64, u64, i8) = match us {
+            WHITE => {
+                let single = (pawns << 8) & empty;
+                (single, ((single & RANK_1 << 16) << 8) & empty, 8)
+            },
+            _ => {
+                let single = (pawns >> 8) & empty;
+                (single, ((single & RANK_8 >> 16) >> 8) & empty, -8)
+            },
+        };
+
+        for to in squares(single) {
+            let from = (to as i8 - forward) as u8;
+            push_pawn_move(moves, from, to, false);
+        }
+
+        for to in squares(double) {
+            let from = (to as i8 - 2 * forward) as u8;
+            moves.push(encode(from, to, MoveFlag::DoublePawnPush));
+        }
+
+        for from in squares(pawns) {
+            for to in squares(pawn_attacks(us, from) & enemy) {
+                push_pawn_move(moves, from, to, true);
+            }
+        }
+
+        if let Some(ep) = self.en_passant {
+            let ep = ep.to_u8();
+
+            // Ignore en passant squares which don't have a pawn that just double pushed
+            // past them, which lenient FENs can contain
+            let victim = (ep as i8 - forward) as u8;
+            let them_pawns = self.piece_bb((us ^ 1) * 6 + WHITE_PAWN);
+
+            if them_pawns & 1 << victim != 0 && occupancy & 1 << ep == 0 {
+                for from in squares(pawn_attacks(us ^ 1, ep) & pawns) {
+                    moves.push(encode(from, ep, MoveFlag::EpCapture));
+                }
+            }
+        }
+    }
+
+    fn generate_castling_moves(&self, moves: &mut Vec<Move>, occupancy: u64) {
+        let us = self.side_to_move;
+        let rights = self.castling_rights >> (us * 2);
+
+        let king = self.piece_bb(us * 6 + WHITE_KING);
+        let rooks = self.piece_bb(us * 6 + WHITE_ROOK);
+        let back_rank = (us as u8 * 7) * 8;
+
+        let king_home = back_rank + 4;
+        if king & 1 << king_home == 0 { return; }
+
+        // Kingside: f & g files empty, rook on h
+        let between = 0b0110_0000u64 << back_rank;
+        if rights & 1 != 0 && occupancy & between == 0 && rooks & 1 << (back_rank + 7) != 0 {
+            moves.push(encode(king_home, king_home + 2, MoveFlag::KingCastle));
+        }
+
+        // Queenside: b, c & d files empty, rook on a
+        let between = 0b0000_1110u64 << back_rank;
+        if rights & 2 != 0 && occupancy & between == 0 && rooks & 1 << back_rank != 0 {
+            moves.push(encode(king_home, king_home - 2, MoveFlag::QueenCastle));
+        }
+    }
+}
+
+fn push_moves(moves: &mut Vec<Move>, from: u8, targets: u64, enemy: u64) {
+    for to in squares(targets) {
+        let flag = match enemy & 1 << to {
+            0 => MoveFlag::Quiet,
+            _ => MoveFlag::Capture,
+        };
+
+        moves.push(encode(from, to, flag));
+    }
+}
+
+/// Pushes a pawn move, expanding it into the four promotions when it reaches the last rank
+fn push_pawn_move(moves: &mut Vec<Move>, from: u8, to: u8, capture: bool) {
+    if (RANK_1 | RANK_8) & 1 << to == 0 {
+        moves.push(encode(from, to, if capture { MoveFlag::Capture } else { MoveFlag::Quiet }));
+        return;
+    }
+
+    for piece in [WHITE_QUEEN, WHITE_KNIGHT, WHITE_ROOK, WHITE_BISHOP] {
+        let flag = MoveFlag::promotion(piece, capture)
+            .expect("Internal error: Invalid promotion piece");
+
+        moves.push(encode(from, to, flag));
+    }
+}
@@ -0,0 +1,233 @@
+use crate::board::*;
+use crate::error::ChessError;
+use crate::r#move::Move;
+use crate::Square;
+
+/// SAN letter for each piece, indexed by white piece index
+const PIECE_LETTERS: [char; 6] = ['K', 'Q', 'R', 'B', 'N', 'P'];
+
+impl Board {
+    /// Parses a move in Standard Algebraic Notation, eg. Nf3, exd5, O-O-O, e8=Q+, Raxd1#
+    ///
+    /// The move is resolved against the legal moves in the position, so the returned move
+    /// has all its flags set. Check, mate and annotation suffixes are accepted but not
+    /// verified.
+    pub fn parse_san(&self, san: &str) -> Result<Move, ChessError> {
+        let text = san.trim_end_matches(['+', '#', '!', '?']);
+
+        let invalid = |msg: &str| ChessError::InvalidSan {
+            msg: format!("{}: {}", msg, san) };
+
+        let legal = self.generate_legal_moves();
+
+        if matches!(text, "O-O" | "0-0" | "O-O-O" | "0-0-0") {
+            let kingside = text.len() == 3;
+
+            return legal.into_iter()
+                .find(|mv| mv.is_castle() && (mv.to().to_u8() % 8 == 6) == kingside)
+                .ok_or_else(|| ChessError::IllegalMove { mv: san.to_string() });
+        }
+
+        let mut chars = text.chars().peekable();
+
+        let piece = match chars.peek() {
+            Some('K') => WHITE_KING,
+            Some('Q') => WHITE_QUEEN,
+            Some('R') => WHITE_ROOK,
+            Some('B') => WHITE_BISHOP,
+            Some('N') => WHITE_KNIGHT,
+            Some('a'..='h') => WHITE_PAWN,
+            _ => return Err(invalid("Expected a piece or file")),
+        };
+
+        if piece != WHITE_PAWN {
+            chars.next();
+        }
+
+        // Everything up to the promotion suffix: [file][rank][x]<square>
+        let body: Vec<char> = chars.by_ref().take_while(|&c| c != '=').collect();
+        let mut rest: String = chars.collect();
+
+        let mut body = body.as_slice();
+
+        // Promotions can be written with or without the '='
+        if rest.is_empty() && piece == WHITE_PAWN {
+            if let Some((&last, init)) = body.split_last() {
+                if matches!(last, 'Q' | 'R' | 'B' | 'N') {
+                    rest.push(last);
+                    body = init;
+                }
+            }
+        }
+
+        let promotion = match rest.as_str() {
+            "" => None,
+            "Q" => Some(WHITE_QUEEN),
+            "R" => Some(WHITE_ROOK),
+            "B" => Some(WHITE_BISHOP),
+            "N" => Some(WHITE_KNIGHT),
+            _ => return Err(invalid("Invalid promotion piece")),
+        };
+
+        if body.len() < 2 {
+            return Err(invalid("Missing target square"));
+        }
+
+        let (qualifier, target) = body.split_at(body.len() - 2);
+
+        let to = target.iter().collect::<String>().parse::<Square>()
+            .map_err(|_| invalid("Invalid target square"))?;
+
+        let (qualifier, capture) = match qualifier.split_last() {
+            Some(('x', init)) => (init, true),
+            _ => (qualifier, false),
+        };
+
+        let mut from_file = None;
+        let mut from_rank = None;
+
+        for &c in qualifier {
+            match c {
+                'a'..='h' if from_file.is_none() && from_rank.is_none() =>
+                    from_file = Some(c as u8 - b'a'),
+                '1'..='8' if from_rank.is_none() =>
+                    from_rank = Some(c as u8 - b'1'),
+                _ => return Err(invalid("Invalid disambiguation")),
+            }
+        }
+
+        if piece == WHITE_PAWN && capture != from_file.is_some() {
+            return Err(invalid("Pawn captures must give the file moved from"));
+        }
+
+        let offset = self.side_to_move * 6;
+
+        let mut candidates = legal.into_iter().filter(|mv| {
+            let from = mv.from().to_u8();
+
+            mv.to() == to
+                && !mv.is_castle()
+                && self.piece_on(mv.from()) == Some(offset + piece)
+                && mv.promotion_piece() == promotion
+                && from_file.is_none_or(|file| from % 8 == file)
+                && from_rank.is_none_or(|rank| from / 8 == rank)
+        });
+
+        match (candidates.next(), candidates.next()) {
+            (Some(mv), None) if mv.is_capture() != capture => Err(invalid(match capture {
+                true => "Capture of an empty square",
+                false => "Capture without an x",
+            })),
+            (Some(mv), None) => Ok(mv),
+            (None, _) => Err(ChessError::IllegalMove { mv: san.to_string() }),
+            (Some(_), Some(_)) => Err(ChessError::AmbiguousMove { san: san.to_string() }),
+        }
+    }
+
+    /// Parses a move in coordinate notation, eg. e2e4, e7e8q, e1g1, and returns the legal
+    /// move it names with all its flags set.
+    ///
+    /// Castling may also be written as the king capturing its own rook, eg. e1h1, as
+    /// Chess960 GUIs and Polyglot books do.
+    pub fn parse_uci_move(&self, uci: &str) -> Result<Move, ChessError> {
+        let parsed = Move::from_algebraic_simple(uci)?;
+
+        let from = parsed.from();
+        let mut to = parsed.to();
+
+        let offset = self.side_to_move * 6;
+
+        if self.piece_on(from) == Some(offset + WHITE_KING)
+            && self.piece_on(to) == Some(offset + WHITE_ROOK)
+            && from.to_u8() / 8 == to.to_u8() / 8
+        {
+            let file = if to.to_u8() > from.to_u8() { 6 } else { 2 };
+            to = Square::from_file_rank(file, from.to_u8() as usize / 8);
+        }
+
+        self.generate_legal_moves()
+            .into_iter()
+            .find(|mv| {
+                mv.from() == from
+                    && mv.to() == to
+                    && mv.promotion_piece() == parsed.promotion_piece()
+            })
+            .ok_or_else(|| ChessError::IllegalMove { mv: uci.to_string() })
+    }
+
+    /// Formats a legal move in Standard Algebraic Notation, with the minimal disambiguation
+    /// and a check or mate suffix
+    pub fn to_san(&self, mv: Move) -> String {
+        let mut san = self.to_san_without_suffix(mv);
+
+        let mut after = self.clone();
+        after.make_move(mv);
+
+        if after.in_check() {
+            san.push(if after.generate_legal_moves().is_empty() { '#' } else { '+' });
+        }
+
+        san
+    }
+
+    fn to_san_without_suffix(&self, mv: Move) -> String {
+        if mv.is_castle() {
+            return match mv.to().to_u8() % 8 {
+                6 => "O-O".to_string(),
+                _ => "O-O-O".to_string(),
+            };
+        }
+
+        let from = mv.from();
+        let to = mv.to();
+
+        let piece = self.piece_on(from)
+            .expect("Internal error: to_san from an empty square") % 6;
+
+        let mut san = String::new();
+
+        if piece == WHITE_PAWN {
+            if mv.is_capture() {
+                san.push((b'a' + from.to_u8() % 8) as char);
+            }
+        } else {
+            san.push(PIECE_LETTERS[piece]);
+
+            // Other pieces of the same kind which could also move to the target square
+            let rivals: Vec<Square> = self.generate_legal_moves()
+                .into_iter()
+                .filter(|other| other.to() == to && other.from() != from)
+                .filter(|other| self.piece_on(other.from()) == self.piece_on(from))
+                .map(|other| other.from())
+                .collect();
+
+            if !rivals.is_empty() {
+                let shares_file = rivals.iter().any(|sq| sq.to_u8() % 8 == from.to_u8() % 8);
+                let shares_rank = rivals.iter().any(|sq| sq.to_u8() / 8 == from.to_u8() / 8);
+
+                let algebraic = from.to_algebraic();
+
+                if !shares_file {
+                    san.push_str(&algebraic[0..1]);
+                } else if !shares_rank {
+                    san.push_str(&algebraic[1..2]);
+                } else {
+                    san.push_str(&algebraic);
+                }
+            }
+        }
+
+        if mv.is_capture() {
+            san.push('x');
+        }
+
+        san.push_str(&to.to_algebraic());
+
+        if let Some(promoted) = mv.promotion_piece() {
+            san.push('=');
+            san.push(PIECE_LETTERS[promoted]);
+        }
+
+        san
+    }
+}
@@ -0,0 +1,132 @@
+use std::fmt::Display;
+
+use crate::bitboard::LIGHT_SQUARES;
+use crate::board::*;
+
+/// How a game ended, or may be claimed to have ended
+///
+/// https://handbook.fide.com/chapter/E012023 articles 5 and 9
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Checkmate { winner: usize },
+    Stalemate,
+    /// Neither side can mate by any series of legal moves
+    InsufficientMaterial,
+    /// 75 moves by each side without a capture or pawn move, drawn without a claim
+    SeventyFiveMoves,
+    /// The same position five times, drawn without a claim
+    FivefoldRepetition,
+    /// 50 moves by each side without a capture or pawn move, a draw if claimed
+    FiftyMoves,
+    /// The same position three times, a draw if claimed
+    ThreefoldRepetition,
+}
+
+impl Outcome {
+    /// The side that won, `None` for a draw
+    pub fn winner(self) -> Option<usize> {
+        match self {
+            Outcome::Checkmate { winner } => Some(winner),
+            _ => None,
+        }
+    }
+
+    /// Whether the game is over without either side claiming it
+    pub fn is_automatic(self) -> bool {
+        !matches!(self, Outcome::FiftyMoves | Outcome::ThreefoldRepetition)
+    }
+
+    /// The PGN game termination marker
+    pub fn result(self) -> &'static str {
+        match self.winner() {
+            Some(WHITE) => "1-0",
+            Some(_) => "0-1",
+            None => "1/2-1/2",
+        }
+    }
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Outcome::Checkmate { winner: WHITE } => write!(f, "White mates"),
+            Outcome::Checkmate { .. } => write!(f, "Black mates"),
+            Outcome::Stalemate => write!(f, "Stalemate"),
+            Outcome::InsufficientMaterial => write!(f, "Insufficient material"),
+            Outcome::SeventyFiveMoves => write!(f, "75 move rule"),
+            Outcome::FivefoldRepetition => write!(f, "Fivefold repetition"),
+            Outcome::FiftyMoves => write!(f, "50 move rule"),
+            Outcome::ThreefoldRepetition => write!(f, "Threefold repetition"),
+        }
+    }
+}
+
+impl Board {
+    /// How the game has ended in this position, `None` while it goes on
+    ///
+    /// Game-ending outcomes come before the draws a player has to claim, and mate takes
+    /// precedence over the move rules as the last move may both mate and reach the limit.
+    /// Repetitions only count the positions in `move_stack`, so nothing before a FEN.
+    pub fn outcome(&self) -> Option<Outcome> {
+        if self.generate_legal_moves().is_empty() {
+            return Some(match self.in_check() {
+                true => Outcome::Checkmate { winner: self.side_to_move ^ 1 },
+                false => Outcome::Stalemate,
+            });
+        }
+
+        let repetitions = self.repetitions();
+
+        if self.is_insufficient_material() {
+            Some(Outcome::InsufficientMaterial)
+        } else if self.halfmove_clock >= 150 {
+            Some(Outcome::SeventyFiveMoves)
+        } else if repetitions >= 4 {
+            Some(Outcome::FivefoldRepetition)
+        } else if self.halfmove_clock >= 100 {
+            Some(Outcome::FiftyMoves)
+        } else if repetitions >= 2 {
+            Some(Outcome::ThreefoldRepetition)
+        } else {
+            None
+        }
+    }
+
+    /// How many times this position came up before in the game, going back as far as
+    /// the last capture or pawn move
+    pub fn repetitions(&self) -> usize {
+        let reversible = (self.halfmove_clock as usize).min(self.undo_stack.len());
+
+        // Each undo entry has the hash before its move, every other one the same side
+        // to move as now
+        self.undo_stack.iter()
+            .rev()
+            .take(reversible)
+            .skip(1)
+            .step_by(2)
+            .filter(|undo| undo.hash == self.hash())
+            .count()
+    }
+
+    /// Whether neither side has enough material left to mate: king against king and a
+    /// minor piece at most, or only bishops all on the same coloured squares
+    pub fn is_insufficient_material(&self) -> bool {
+        let heavy = [WHITE_QUEEN, WHITE_ROOK, WHITE_PAWN, BLACK_QUEEN, BLACK_ROOK, BLACK_PAWN]
+            .iter()
+            .any(|&piece| self.piece_bb(piece) != 0);
+
+        if heavy {
+            return false;
+        }
+
+        let knights = self.piece_bb(WHITE_KNIGHT) | self.piece_bb(BLACK_KNIGHT);
+        let bishops = self.piece_bb(WHITE_BISHOP) | self.piece_bb(BLACK_BISHOP);
+
+        let light = u64::from(LIGHT_SQUARES);
+
+        match (knights | bishops).count_ones() {
+            0 | 1 => true,
+            _ => knights == 0 && (bishops & light == 0 || bishops & !light == 0),
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use std::io::Write;
+
+use crate::board::Board;
+
+impl Board {
+    /// Counts the leaf nodes of the legal move tree `depth` plies deep
+    pub fn perft(&mut self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        let moves = self.generate_legal_moves();
+
+        // Bulk count the last ply rather than making each move
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+
+        let mut nodes = 0;
+
+        for mv in moves {
+            self.make_move(mv);
+            nodes += self.perft(depth - 1);
+            self.unmake_move();
+        }
+
+        nodes
+    }
+
+    /// Perft split by root move, writing each move's node count to `out`, eg. "e2e4: 20".
+    /// Returns the total node count.
+    pub fn divide<W: Write>(&mut self, depth: u32, out: &mut W) -> std::io::Result<u64> {
+        if depth == 0 {
+            return Ok(1);
+        }
+
+        let mut total = 0;
+
+        for mv in self.generate_legal_moves() {
+            self.make_move(mv);
+            let nodes = self.perft(depth - 1);
+            self.unmake_move();
+
+            writeln!(out, "{}: {}", mv.to_algebraic(), nodes)?;
+            total += nodes;
+        }
+
+        Ok(total)
+    }
+}
@@ -0,0 +1,903 @@
+use std::io::{BufRead, Write};
+use std::time::Duration;
+
+use crate::board::{Board, WHITE};
+use crate::error::ChessError;
+use crate::game::{GameTree, NodeId};
+
+/// Longest line of movetext written
+const LINE_WIDTH: usize = 80;
+
+/// The tags every game has, in the order they're written
+const SEVEN_TAG_ROSTER: [(&str, &str); 7] = [
+    ("Event", "?"),
+    ("Site", "?"),
+    ("Date", "????.??.??"),
+    ("Round", "?"),
+    ("White", "?"),
+    ("Black", "?"),
+    ("Result", "*"),
+];
+
+/// A game as written in PGN, with the moves as SAN text. Nothing is checked against a
+/// board here, the moves are only known to look like SAN.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PgnGame {
+    /// Tag pairs in the order they were given
+    pub tags: Vec<(String, String)>,
+    /// The main line
+    pub moves: Vec<PgnMove>,
+    /// Comments with no move to go with, as in a game without moves
+    pub comments: Vec<String>,
+    /// The game termination marker: 1-0, 0-1, 1/2-1/2 or *
+    pub result: Option<String>,
+}
+
+impl PgnGame {
+    /// The value of the first tag called `name`
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PgnMove {
+    pub san: String,
+    /// Numeric Annotation Glyphs, `$n` or a suffix like `!?`
+    pub nags: Vec<u8>,
+    /// Comments before the move, only found at the start of a game or variation
+    pub comments_before: Vec<String>,
+    /// Comments after the move
+    pub comments: Vec<String>,
+    /// Clock time left after the move, from a `[%clk h:mm:ss]` comment command
+    pub clock: Option<Duration>,
+    /// Engine evaluation after the move, from a `[%eval ...]` comment command
+    pub eval: Option<PgnEval>,
+    /// Alternatives to this move, each a line starting in the same position
+    pub variations: Vec<Vec<PgnMove>>,
+}
+
+/// An evaluation in a `[%eval]` comment command, from White's point of view
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PgnEval {
+    /// Written in pawns, eg. `[%eval -1.25]`
+    Centipawns(i32),
+    /// Moves to mate, negative when Black mates, eg. `[%eval #-3]`
+    Mate(i32),
+}
+
+/// Reads games one at a time from PGN text, holding no more than the current game and
+/// line in memory
+///
+/// Games that can't be parsed come out as `ChessError::InvalidPgn` with the line and
+/// column of the problem, and reading carries on with the next game.
+///
+/// http://www.saremba.de/chessgml/standards/pgn/pgn-complete.htm
+pub struct PgnReader<R: BufRead> {
+    reader: R,
+    line: String,
+    /// Byte offset of the next character in `line`
+    pos: usize,
+    /// Number of the line in `line`, counting from 1
+    line_no: usize,
+    done: bool,
+}
+
+impl<R: BufRead> PgnReader<R> {
+    pub fn new(reader: R) -> Self {
+        PgnReader {
+            reader,
+            line: String::new(),
+            pos: 0,
+            line_no: 0,
+            done: false,
+        }
+    }
+
+    /// The next game, `None` at the end of the input
+    pub fn read_game(&mut self) -> Result<Option<PgnGame>, ChessError> {
+        self.skip_whitespace()?;
+
+        if self.peek()?.is_none() {
+            return Ok(None);
+        }
+
+        let mut game = PgnGame::default();
+
+        while self.skip_whitespace()? == Some('[') {
+            game.tags.push(self.read_tag()?);
+        }
+
+        // The line being read, with the main line at the bottom and the variation being
+        // read at the top
+        let mut lines: Vec<Vec<PgnMove>> = vec![Vec::new()];
+        let mut comments_before = Vec::new();
+
+        loop {
+            let Some(c) = self.skip_whitespace()? else {
+                if lines.len() > 1 {
+                    return Err(self.error("Unterminated variation"));
+                }
+
+                break;
+            };
+
+            let depth = lines.len();
+            let line = lines.last_mut().expect("Internal error: no line being read");
+
+            match c {
+                // The next game's tags, this one had no termination marker
+                '[' if depth == 1 => break,
+                '[' => return Err(self.error("Unterminated variation")),
+                '{' | ';' => {
+                    let comment = self.read_comment()?;
+
+                    match line.last_mut() {
+                        Some(mv) if comments_before.is_empty() => {
+                            let comment = take_commands(mv, &comment);
+
+                            if !comment.is_empty() {
+                                mv.comments.push(comment);
+                            }
+                        },
+                        _ => comments_before.push(comment),
+                    }
+                },
+                '(' => {
+                    if line.is_empty() {
+                        return Err(self.error("Variation without a move before it"));
+                    }
+
+                    self.bump();
+                    lines.push(Vec::new());
+                },
+                ')' => {
+                    if depth == 1 {
+                        return Err(self.error("Unmatched )"));
+                    }
+
+                    let mut variation = lines.pop().expect("Internal error: no variation to close");
+
+                    match variation.last_mut() {
+                        Some(mv) => mv.comments.append(&mut comments_before),
+                        None => return Err(self.error("Empty variation")),
+                    }
+
+                    self.bump();
+
+                    lines.last_mut()
+                        .and_then(|line| line.last_mut())
+                        .expect("Internal error: variation without a move")
+                        .variations.push(variation);
+                },
+                '$' | '!' | '?' => {
+                    let nag = self.read_nag()?;
+
+                    match line.last_mut() {
+                        Some(mv) if comments_before.is_empty() => mv.nags.push(nag),
+                        _ => return Err(self.error("Annotation without a move")),
+                    }
+                },
+                _ => {
+                    let column = self.column();
+                    let token = self.read_token()?;
+
+                    if matches!(token.as_str(), "1-0" | "0-1" | "1/2-1/2" | "*") {
+                        if depth > 1 {
+                            return Err(self.error_at(column, "Game result inside a variation"));
+                        }
+
+                        game.result = Some(token);
+                        break;
+                    }
+
+                    // Move numbers, eg. 12. or 12... which may run into the move
+                    let digits = token.len() - token.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+                    let san = match token[digits..].starts_with('.') {
+                        true => token[digits..].trim_start_matches('.'),
+                        false => token.as_str(),
+                    };
+
+                    if san.is_empty() {
+                        continue;
+                    }
+
+                    if !is_san(san) {
+                        return Err(self.error_at(column, &format!("Expected a move, found {}", token)));
+                    }
+
+                    line.push(PgnMove {
+                        san: san.to_string(),
+                        comments_before: std::mem::take(&mut comments_before),
+                        ..PgnMove::default()
+                    });
+                },
+            }
+        }
+
+        game.moves = lines.pop().expect("Internal error: no main line");
+
+        match game.moves.last_mut() {
+            Some(mv) => mv.comments.append(&mut comments_before),
+            None => game.comments = comments_before,
+        }
+
+        Ok(Some(game))
+    }
+
+    /// `[Name "value"]`, the value may escape `"` and `\` with a backslash
+    fn read_tag(&mut self) -> Result<(String, String), ChessError> {
+        self.bump();
+        self.skip_whitespace()?;
+
+        let mut name = String::new();
+
+        while let Some(c) = self.peek()?.filter(|&c| c.is_ascii_alphanumeric() || c == '_') {
+            name.push(c);
+            self.bump();
+        }
+
+        if name.is_empty() {
+            return Err(self.error("Expected a tag name"));
+        }
+
+        if self.skip_whitespace()? != Some('"') {
+            return Err(self.error("Expected a tag value in quotes"));
+        }
+
+        self.bump();
+
+        let mut value = String::new();
+
+        loop {
+            match self.peek()? {
+                Some('"') => break,
+                Some('\\') => {
+                    self.bump();
+
+                    match self.peek()? {
+                        Some(c @ ('"' | '\\')) => value.push(c),
+                        _ => return Err(self.error("Invalid escape in tag value")),
+                    }
+                },
+                Some('\n') | None => return Err(self.error("Unterminated tag value")),
+                Some(c) => value.push(c),
+            }
+
+            self.bump();
+        }
+
+        self.bump();
+
+        if self.skip_whitespace()? != Some(']') {
+            return Err(self.error("Expected ] after the tag value"));
+        }
+
+        self.bump();
+
+        Ok((name, value))
+    }
+
+    /// `{...}`, which may run over several lines, or `;` to the end of the line
+    fn read_comment(&mut self) -> Result<String, ChessError> {
+        let (line, column) = (self.line_no, self.column());
+
+        let end = match self.peek()? {
+            Some('{') => '}',
+            _ => '\n',
+        };
+
+        self.bump();
+
+        let mut comment = String::new();
+
+        loop {
+            match self.peek()? {
+                Some(c) if c == end => break,
+                Some(c) => comment.push(c),
+                None if end == '\n' => break,
+                None => return Err(ChessError::InvalidPgn { line, column, msg: "Unterminated comment".to_string() }),
+            }
+
+            self.bump();
+        }
+
+        self.bump();
+
+        Ok(comment.trim().to_string())
+    }
+
+    /// `$n`, or one of the move suffixes `!`, `?`, `!!`, `??`, `!?` and `?!`
+    fn read_nag(&mut self) -> Result<u8, ChessError> {
+        let column = self.column();
+
+        if self.peek()? == Some('$') {
+            self.bump();
+
+            let mut digits = String::new();
+
+            while let Some(c) = self.peek()?.filter(char::is_ascii_digit) {
+                digits.push(c);
+                self.bump();
+            }
+
+            return digits.parse().map_err(|_| self.error_at(column, "Invalid NAG"));
+        }
+
+        let mut suffix = String::new();
+
+        while let Some(c) = self.peek()?.filter(|&c| c == '!' || c == '?') {
+            suffix.push(c);
+            self.bump();
+        }
+
+        match suffix.as_str() {
+            "!" => Ok(1),
+            "?" => Ok(2),
+            "!!" => Ok(3),
+            "??" => Ok(4),
+            "!?" => Ok(5),
+            "?!" => Ok(6),
+            _ => Err(self.error_at(column, &format!("Invalid move suffix {}", suffix))),
+        }
+    }
+
+    /// A move, move number or result, up to whitespace or the next piece of movetext
+    fn read_token(&mut self) -> Result<String, ChessError> {
+        let mut token = String::new();
+
+        while let Some(c) = self.peek()? {
+            if c.is_whitespace() || "{};()$!?[]\"".contains(c) {
+                break;
+            }
+
+            token.push(c);
+            self.bump();
+        }
+
+        if token.is_empty() {
+            let c = self.peek()?.unwrap_or(' ');
+            return Err(self.error(&format!("Unexpected {}", c)));
+        }
+
+        Ok(token)
+    }
+
+    /// Skips whitespace and `%` escaped lines, returning the next character
+    fn skip_whitespace(&mut self) -> Result<Option<char>, ChessError> {
+        loop {
+            match self.peek()? {
+                Some('%') if self.pos == 0 => self.pos = self.line.len(),
+                Some(c) if c.is_whitespace() => self.bump(),
+                c => return Ok(c),
+            }
+        }
+    }
+
+    /// The next character, reading another line when this one is used up
+    fn peek(&mut self) -> Result<Option<char>, ChessError> {
+        while self.pos >= self.line.len() {
+            if self.done {
+                return Ok(None);
+            }
+
+            self.line.clear();
+            self.pos = 0;
+
+            let read = self.reader.read_line(&mut self.line).map_err(|e| {
+                self.done = true;
+                ChessError::Io(e)
+            })?;
+
+            if read == 0 {
+                self.done = true;
+                return Ok(None);
+            }
+
+            self.line_no += 1;
+        }
+
+        Ok(self.line[self.pos..].chars().next())
+    }
+
+    fn bump(&mut self) {
+        if let Some(c) = self.line[self.pos..].chars().next() {
+            self.pos += c.len_utf8();
+        }
+    }
+
+    fn column(&self) -> usize {
+        self.line[..self.pos].chars().count() + 1
+    }
+
+    fn error(&self, msg: &str) -> ChessError {
+        self.error_at(self.column(), msg)
+    }
+
+    fn error_at(&self, column: usize, msg: &str) -> ChessError {
+        ChessError::InvalidPgn { line: self.line_no, column, msg: msg.to_string() }
+    }
+
+    /// Skips the rest of a malformed game, up to the next tag following some movetext
+    fn skip_to_next_game(&mut self) -> Result<(), ChessError> {
+        // Stopped by the next game's tags
+        if self.line[..self.pos].trim().is_empty() && self.line[self.pos..].starts_with('[') {
+            return Ok(());
+        }
+
+        let mut seen_movetext = !self.line.trim_start().starts_with('[');
+
+        self.pos = self.line.len();
+
+        while self.peek()?.is_some() {
+            let trimmed = self.line.trim_start();
+
+            if trimmed.starts_with('[') && seen_movetext {
+                self.pos = self.line.len() - trimmed.len();
+                break;
+            }
+
+            seen_movetext |= !trimmed.starts_with('[');
+            self.pos = self.line.len();
+        }
+
+        Ok(())
+    }
+}
+
+impl<R: BufRead> Iterator for PgnReader<R> {
+    type Item = Result<PgnGame, ChessError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.read_game() {
+            Ok(game) => game.map(Ok),
+            Err(e) => {
+                if let Err(io) = self.skip_to_next_game() {
+                    return Some(Err(io));
+                }
+
+                Some(Err(e))
+            },
+        }
+    }
+}
+
+/// Whether a token looks like a move in SAN, eg. e4, Nbxd2+, e8=Q#, O-O-O
+fn is_san(token: &str) -> bool {
+    let body = token.trim_end_matches(['+', '#']);
+
+    if matches!(body, "O-O" | "O-O-O" | "0-0" | "0-0-0") {
+        return true;
+    }
+
+    let mut chars = body.chars();
+
+    matches!(chars.next(), Some('a'..='h' | 'K' | 'Q' | 'R' | 'B' | 'N'))
+        && body.chars().any(|c| c.is_ascii_digit())
+        && chars.all(|c| matches!(c, 'a'..='h' | '1'..='8' | 'x' | '=' | 'Q' | 'R' | 'B' | 'N'))
+}
+
+/// Takes the `[%clk]` and `[%eval]` commands out of a comment after `mv`, returning the
+/// rest of the comment. Other commands are left in.
+fn take_commands(mv: &mut PgnMove, comment: &str) -> String {
+    let mut rest = String::new();
+    let mut text = comment;
+
+    while let Some(start) = text.find("[%") {
+        let Some(len) = text[start..].find(']') else { break; };
+        let command = &text[start + 2..start + len];
+
+        let taken = match command.split_once(' ') {
+            Some(("clk", value)) => parse_clock(value.trim()).map(|clock| mv.clock = Some(clock)),
+            Some(("eval", value)) => parse_eval(value.trim()).map(|eval| mv.eval = Some(eval)),
+            _ => None,
+        };
+
+        let end = start + len + 1;
+
+        rest.push_str(&text[..start]);
+
+        if taken.is_none() {
+            rest.push_str(&text[start..end]);
+        }
+
+        text = &text[end..];
+    }
+
+    if mv.clock.is_none() && mv.eval.is_none() {
+        return comment.to_string();
+    }
+
+    rest.push_str(text);
+
+    rest.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// `h:mm:ss`, with optional fractions of a second
+fn parse_clock(value: &str) -> Option<Duration> {
+    let mut parts = value.split(':');
+    let (h, m, s) = (parts.next()?, parts.next()?, parts.next()?);
+
+    if parts.next().is_some() {
+        return None;
+    }
+
+    let secs = h.parse::<u64>().ok()? * 3600 + m.parse::<u64>().ok()? * 60;
+    let s = s.parse::<f64>().ok().filter(|s| (0.0..60.0).contains(s))?;
+
+    Some(Duration::from_secs(secs) + Duration::from_secs_f64(s))
+}
+
+/// Pawns, eg. `0.17`, or moves to mate, eg. `#-3`
+fn parse_eval(value: &str) -> Option<PgnEval> {
+    match value.strip_prefix('#') {
+        Some(mate) => mate.parse().ok().map(PgnEval::Mate),
+        None => value.parse::<f64>().ok()
+            .filter(|pawns| pawns.is_finite())
+            .map(|pawns| PgnEval::Centipawns((pawns * 100.0).round() as i32)),
+    }
+}
+
+fn format_clock(clock: Duration) -> String {
+    let secs = clock.as_secs();
+    let tenths = clock.subsec_millis() / 100;
+
+    match tenths {
+        0 => format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60),
+        _ => format!("{}:{:02}:{:02}.{}", secs / 3600, secs / 60 % 60, secs % 60, tenths),
+    }
+}
+
+fn format_eval(eval: PgnEval) -> String {
+    match eval {
+        PgnEval::Mate(moves) => format!("#{}", moves),
+        PgnEval::Centipawns(cp) => {
+            let sign = if cp < 0 { "-" } else { "" };
+            format!("{}{}.{:02}", sign, cp.abs() / 100, cp.abs() % 100)
+        },
+    }
+}
+
+impl PgnGame {
+    /// The game in PGN export format, see `write_game`
+    pub fn to_pgn(&self) -> Result<String, ChessError> {
+        let mut out = Vec::new();
+        write_game(&mut out, self)?;
+
+        Ok(String::from_utf8(out).expect("Internal error: PGN isn't UTF-8"))
+    }
+
+    /// The moves checked against the board, starting from the `FEN` tag or the start
+    /// position. Clock times and evaluations become the `clk` and `eval` annotations.
+    pub fn to_tree(&self) -> Result<GameTree, ChessError> {
+        let board = match self.tag("FEN") {
+            Some(fen) => Board::from_fen(fen)?,
+            None => Board::startpos(),
+        };
+
+        let mut tree = GameTree::new(board);
+        let root = tree.root();
+
+        tree.node_mut(root).comments = self.comments.clone();
+        add_line(&mut tree, root, &self.moves)?;
+
+        Ok(tree)
+    }
+
+    /// A game with the moves of `tree` in SAN, and `FEN` and `SetUp` tags when it doesn't
+    /// start from the start position. Annotations other than `clk` and `eval` are kept as
+    /// commands at the front of the first comment.
+    pub fn from_tree(tree: &GameTree) -> PgnGame {
+        let mut game = PgnGame::default();
+        let board = tree.root_board();
+
+        if board.to_fen() != Board::startpos().to_fen() {
+            game.tags.push(("SetUp".to_string(), "1".to_string()));
+            game.tags.push(("FEN".to_string(), board.to_fen()));
+        }
+
+        game.comments = tree.node(tree.root()).comments.clone();
+        game.moves = line_from_tree(tree, &mut board.clone(), tree.root());
+
+        game
+    }
+}
+
+/// Adds `moves` and their variations to `tree` after `parent`
+fn add_line(tree: &mut GameTree, parent: NodeId, moves: &[PgnMove]) -> Result<(), ChessError> {
+    let mut parent = parent;
+
+    for mv in moves {
+        let id = tree.add_san(parent, &mv.san)?;
+        let node = tree.node_mut(id);
+
+        node.comments_before = mv.comments_before.clone();
+        node.comments = mv.comments.clone();
+        node.nags = mv.nags.clone();
+
+        if let Some(eval) = mv.eval {
+            node.set_annotation("eval", &format_eval(eval));
+        }
+
+        if let Some(clock) = mv.clock {
+            node.set_annotation("clk", &format_clock(clock));
+        }
+
+        for variation in &mv.variations {
+            add_line(tree, parent, variation)?;
+        }
+
+        parent = id;
+    }
+
+    Ok(())
+}
+
+/// The main line after `parent`, with the variations on it, played from `board`
+fn line_from_tree(tree: &GameTree, board: &mut Board, parent: NodeId) -> Vec<PgnMove> {
+    let mut moves = Vec::new();
+    let mut parent = parent;
+
+    while let Some((&main, variations)) = tree.node(parent).children().split_first() {
+        let mut mv = pgn_move(tree, board, main);
+
+        mv.variations = variations.iter()
+            .map(|&id| {
+                let mut board = board.clone();
+                let mut line = vec![pgn_move(tree, &mut board, id)];
+                line.extend(line_from_tree(tree, &mut board, id));
+                line
+            })
+            .collect();
+
+        moves.push(mv);
+        parent = main;
+    }
+
+    moves
+}
+
+/// The move at `id` without its variations, made on `board`
+fn pgn_move(tree: &GameTree, board: &mut Board, id: NodeId) -> PgnMove {
+    let node = tree.node(id);
+    let legal = node.mv().expect("Internal error: only the root has no move");
+
+    let mut mv = PgnMove {
+        san: board.to_san(legal),
+        nags: node.nags.clone(),
+        comments_before: node.comments_before.clone(),
+        comments: node.comments.clone(),
+        ..PgnMove::default()
+    };
+
+    let mut others = Vec::new();
+
+    for (name, value) in &node.annotations {
+        let taken = match name.as_str() {
+            "clk" => parse_clock(value).map(|clock| mv.clock = Some(clock)),
+            "eval" => parse_eval(value).map(|eval| mv.eval = Some(eval)),
+            _ => None,
+        };
+
+        if taken.is_none() {
+            others.push(format!("[%{} {}]", name, value));
+        }
+    }
+
+    if !others.is_empty() {
+        match mv.comments.first_mut() {
+            Some(first) => *first = format!("{} {}", others.join(" "), first),
+            None => mv.comments.push(others.join(" ")),
+        }
+    }
+
+    board.make_move(legal);
+
+    mv
+}
+
+/// Writes `game` in PGN export format: the Seven Tag Roster first, filled in with `?`
+/// where missing, then the other tags in alphabetical order, then the movetext wrapped
+/// at 80 columns and a blank line.
+///
+/// The moves are checked by building a `GameTree` and written in SAN as `Board` formats
+/// them, so any illegal move is an error.
+pub fn write_game<W: Write>(out: &mut W, game: &PgnGame) -> Result<(), ChessError> {
+    let result = game.result.as_deref()
+        .or(game.tag("Result"))
+        .unwrap_or("*");
+
+    // Everything is checked before any of it is written
+    let tree = game.to_tree()?;
+
+    let mut movetext = MovetextWriter { number_next: true, ..MovetextWriter::default() };
+
+    for comment in &tree.node(tree.root()).comments {
+        movetext.comment(comment);
+    }
+
+    movetext.line(&tree, &mut tree.root_board().clone(), tree.root());
+    movetext.push(result);
+
+    for (name, default) in SEVEN_TAG_ROSTER {
+        let value = match name {
+            "Result" => Some(result),
+            _ => game.tag(name),
+        };
+
+        write_tag(out, name, value.unwrap_or(default))?;
+    }
+
+    let mut others: Vec<_> = game.tags.iter()
+        .filter(|(name, _)| SEVEN_TAG_ROSTER.iter().all(|(roster, _)| roster != name))
+        .collect();
+
+    others.sort_by(|a, b| a.0.cmp(&b.0));
+
+    for (name, value) in others {
+        write_tag(out, name, value)?;
+    }
+
+    writeln!(out)?;
+
+    for line in movetext.finish() {
+        writeln!(out, "{}", line)?;
+    }
+
+    writeln!(out)?;
+
+    Ok(())
+}
+
+fn write_tag<W: Write>(out: &mut W, name: &str, value: &str) -> std::io::Result<()> {
+    writeln!(out, "[{} \"{}\"]", name, value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Columns taken by `text`, counting characters rather than bytes
+fn width(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// Lays out movetext a token at a time, breaking lines at spaces
+#[derive(Default)]
+struct MovetextWriter {
+    lines: Vec<String>,
+    current: String,
+    /// Whether the next black move needs its number, as at the start of a line and after
+    /// comments and variations
+    number_next: bool,
+    /// Whether the next token follows on without a space, as after `(`
+    glue_next: bool,
+}
+
+impl MovetextWriter {
+    fn push(&mut self, token: &str) {
+        if std::mem::take(&mut self.glue_next) {
+            return self.glue(token);
+        }
+
+        if !self.current.is_empty() && width(&self.current) + 1 + width(token) > LINE_WIDTH {
+            self.lines.push(std::mem::take(&mut self.current));
+        }
+
+        if !self.current.is_empty() {
+            self.current.push(' ');
+        }
+
+        self.current.push_str(token);
+    }
+
+    /// Adds `token` to the end of the last one, taking the last one on to the next line
+    /// with it when they don't fit
+    fn glue(&mut self, token: &str) {
+        if width(&self.current) + width(token) > LINE_WIDTH {
+            if let Some((init, last)) = self.current.rsplit_once(' ') {
+                let last = last.to_string();
+                self.current.truncate(init.len());
+                self.lines.push(std::mem::replace(&mut self.current, last));
+            }
+        }
+
+        self.current.push_str(token);
+    }
+
+    /// A comment, which may be broken over lines between words. A `}` would end it
+    /// early and there's no escaping it in PGN, so any are left out.
+    fn comment(&mut self, comment: &str) {
+        let comment = comment.replace('}', "");
+        let words: Vec<_> = comment.split_whitespace().collect();
+
+        match words.as_slice() {
+            [] => self.push("{}"),
+            [word] => self.push(&format!("{{{}}}", word)),
+            [first, middle @ .., last] => {
+                self.push(&format!("{{{}", first));
+
+                for word in middle {
+                    self.push(word);
+                }
+
+                self.push(&format!("{}}}", last));
+            },
+        }
+
+        self.number_next = true;
+    }
+
+    /// Writes the main line after `parent` played from `board`, and the variations on
+    /// it, leaving `board` at the end of the line
+    fn line(&mut self, tree: &GameTree, board: &mut Board, parent: NodeId) {
+        let mut parent = parent;
+
+        while let Some((&main, variations)) = tree.node(parent).children().split_first() {
+            self.node(tree, board, main);
+
+            for &variation in variations {
+                self.push("(");
+                self.glue_next = true;
+                self.number_next = true;
+
+                let mut board = board.clone();
+                self.node(tree, &board, variation);
+                board.make_move(tree.node(variation).mv().expect("Internal error: only the root has no move"));
+                self.line(tree, &mut board, variation);
+
+                self.glue(")");
+                self.number_next = true;
+            }
+
+            board.make_move(tree.node(main).mv().expect("Internal error: only the root has no move"));
+            parent = main;
+        }
+    }
+
+    /// Writes the move at `id` played from `board`, with its comments and annotations
+    fn node(&mut self, tree: &GameTree, board: &Board, id: NodeId) {
+        let node = tree.node(id);
+        let legal = node.mv().expect("Internal error: only the root has no move");
+
+        for comment in &node.comments_before {
+            self.comment(comment);
+        }
+
+        // Numbers stay on the same line as their moves
+        let san = board.to_san(legal);
+        let number = board.fullmove_counter;
+
+        match board.side_to_move {
+            WHITE => self.push(&format!("{}. {}", number, san)),
+            _ if self.number_next => self.push(&format!("{}... {}", number, san)),
+            _ => self.push(&san),
+        }
+
+        self.number_next = false;
+
+        for nag in &node.nags {
+            self.push(&format!("${}", nag));
+        }
+
+        let commands: Vec<_> = node.annotations.iter()
+            .map(|(name, value)| format!("[%{} {}]", name, value))
+            .collect();
+
+        // Commands go in front of the first comment
+        match (commands.is_empty(), node.comments.split_first()) {
+            (true, _) => node.comments.iter().for_each(|comment| self.comment(comment)),
+            (false, None) => self.comment(&commands.join(" ")),
+            (false, Some((first, rest))) => {
+                self.comment(&format!("{} {}", commands.join(" "), first));
+                rest.iter().for_each(|comment| self.comment(comment));
+            },
+        }
+    }
+
+    fn finish(mut self) -> Vec<String> {
+        if !self.current.is_empty() {
+            self.lines.push(self.current);
+        }
+
+        self.lines
+    }
+}
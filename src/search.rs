@@ -0,0 +1,390 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::board::Board;
+use crate::eval::{evaluate, PIECE_VALUES};
+use crate::r#move::Move;
+use crate::tt::{Bound, TranspositionTable};
+
+/// Score of being checkmated at the root, mates further away score closer to zero
+pub const MATE: i32 = 30_000;
+pub const MAX_PLY: usize = 128;
+
+/// Any score at least this large is a forced mate
+pub const MATE_BOUND: i32 = MATE - MAX_PLY as i32;
+
+const INFINITY: i32 = 32_000;
+
+/// Margin on top of the captured piece for delta pruning in quiescence search
+const DELTA_MARGIN: i32 = 200;
+
+/// How often the clock and stop flag are checked, in nodes
+const CHECK_INTERVAL: u64 = 1024;
+
+/// When to stop searching, unlimited in every way by default
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchLimits {
+    pub depth: Option<u32>,
+    pub nodes: Option<u64>,
+    pub time: Option<Duration>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResult {
+    pub best_move: Option<Move>,
+    /// Centipawns from the point of view of the side to move, see `mate_in`
+    pub score: i32,
+    pub pv: Vec<Move>,
+    pub depth: u32,
+    pub nodes: u64,
+    /// Permille of the hash table filled by this search
+    pub hashfull: usize,
+}
+
+impl SearchResult {
+    /// Moves until mate when the score is a forced mate, negative when the side to move
+    /// is getting mated
+    pub fn mate_in(&self) -> Option<i32> {
+        match self.score {
+            score if score >= MATE_BOUND => Some((MATE - score + 1) / 2),
+            score if score <= -MATE_BOUND => Some(-(MATE + score) / 2),
+            _ => None,
+        }
+    }
+}
+
+/// Time to spend on the next move given the clock, the increment and how many moves
+/// there are until the next time control
+pub fn allocate_time(time_left: Duration, increment: Duration, moves_to_go: Option<u32>) -> Duration {
+    let moves_to_go = moves_to_go.unwrap_or(30).clamp(1, 50);
+
+    let budget = time_left / moves_to_go + increment * 3 / 4;
+
+    // Always leave something on the clock for communication overhead
+    budget.min(time_left.saturating_sub(Duration::from_millis(50)) / 2)
+}
+
+/// Searches `board` with iterative deepening negamax alpha-beta until one of the `limits`
+/// is reached or `stop` is set, calling `report` after every completed iteration.
+///
+/// Results are shared with later searches through `tt`. The board is left as it was found.
+pub fn search<F: FnMut(&SearchResult)>(
+    board: &mut Board,
+    tt: &mut TranspositionTable,
+    limits: &SearchLimits,
+    stop: &AtomicBool,
+    mut report: F,
+) -> SearchResult {
+    tt.new_search();
+
+    let mut searcher = Searcher {
+        tt,
+        limits,
+        stop,
+        start: Instant::now(),
+        nodes: 0,
+        stopped: false,
+    };
+
+    let mut result = SearchResult {
+        best_move: board.generate_legal_moves().first().copied(),
+        score: 0,
+        pv: Vec::new(),
+        depth: 0,
+        nodes: 0,
+        hashfull: 0,
+    };
+
+    let max_depth = limits.depth.unwrap_or(MAX_PLY as u32).clamp(1, MAX_PLY as u32 - 1);
+
+    for depth in 1..=max_depth {
+        let mut pv = Vec::new();
+        let score = searcher.root(board, depth, &result.pv, &mut pv);
+
+        // A partial iteration can't be trusted, keep the last complete one
+        if searcher.stopped {
+            break;
+        }
+
+        result = SearchResult {
+            best_move: pv.first().copied(),
+            score,
+            pv,
+            depth,
+            nodes: searcher.nodes,
+            hashfull: searcher.tt.hashfull(),
+        };
+
+        report(&result);
+
+        // Nothing more to learn once a forced mate is found
+        if score.abs() >= MATE_BOUND && (MATE - score.abs()) as u32 <= depth {
+            break;
+        }
+    }
+
+    result.nodes = searcher.nodes;
+
+    result
+}
+
+struct Searcher<'a> {
+    tt: &'a mut TranspositionTable,
+    limits: &'a SearchLimits,
+    stop: &'a AtomicBool,
+    start: Instant,
+    nodes: u64,
+    stopped: bool,
+}
+
+impl Searcher<'_> {
+    fn should_stop(&mut self) -> bool {
+        if self.stopped {
+            return true;
+        }
+
+        if self.limits.nodes.is_some_and(|nodes| self.nodes >= nodes) {
+            self.stopped = true;
+        }
+
+        if self.nodes.is_multiple_of(CHECK_INTERVAL) {
+            if self.stop.load(Ordering::Relaxed) {
+                self.stopped = true;
+            }
+
+            if self.limits.time.is_some_and(|time| self.start.elapsed() >= time) {
+                self.stopped = true;
+            }
+        }
+
+        self.stopped
+    }
+
+    fn root(&mut self, board: &mut Board, depth: u32, prev_pv: &[Move], pv: &mut Vec<Move>) -> i32 {
+        let mut moves = board.generate_legal_moves();
+
+        if moves.is_empty() {
+            return if board.in_check() { -MATE } else { 0 };
+        }
+
+        order_moves(board, &mut moves, prev_pv.first().copied());
+
+        let mut alpha = -INFINITY;
+        let beta = INFINITY;
+        let mut child_pv = Vec::new();
+
+        for mv in moves {
+            board.make_move(mv);
+            let score = -self.negamax(board, depth - 1, 1, -beta, -alpha, &mut child_pv);
+            board.unmake_move();
+
+            if self.stopped {
+                break;
+            }
+
+            if score > alpha {
+                alpha = score;
+
+                pv.clear();
+                pv.push(mv);
+                pv.append(&mut child_pv);
+            }
+        }
+
+        alpha
+    }
+
+    fn negamax(
+        &mut self,
+        board: &mut Board,
+        depth: u32,
+        ply: usize,
+        mut alpha: i32,
+        beta: i32,
+        pv: &mut Vec<Move>,
+    ) -> i32 {
+        pv.clear();
+        self.nodes += 1;
+
+        if self.should_stop() {
+            return 0;
+        }
+
+        // One repetition is enough to score a draw, the side it suits can repeat again
+        if board.repetitions() > 0 || board.is_insufficient_material() {
+            return 0;
+        }
+
+        // Mate on the move that reaches the limit still counts
+        if board.halfmove_clock >= 100 {
+            return match board.in_check() && board.generate_legal_moves().is_empty() {
+                true => -MATE + ply as i32,
+                false => 0,
+            };
+        }
+
+        if depth == 0 {
+            return self.quiescence(board, ply, alpha, beta);
+        }
+
+        if ply >= MAX_PLY - 1 {
+            return evaluate(board);
+        }
+
+        let key = board.hash();
+        let mut tt_move = None;
+
+        if let Some(entry) = self.tt.probe(key) {
+            tt_move = entry.best_move();
+
+            if entry.depth() >= depth {
+                let score = entry.score(ply);
+
+                let cutoff = match entry.bound() {
+                    Bound::Exact => true,
+                    Bound::Lower => score >= beta,
+                    Bound::Upper => score <= alpha,
+                };
+
+                if cutoff {
+                    return score.clamp(alpha, beta);
+                }
+            }
+        }
+
+        let mut moves = board.generate_legal_moves();
+
+        if moves.is_empty() {
+            return if board.in_check() { -MATE + ply as i32 } else { 0 };
+        }
+
+        order_moves(board, &mut moves, tt_move);
+
+        let mut child_pv = Vec::new();
+        let mut best_move = None;
+        let mut bound = Bound::Upper;
+
+        for mv in moves {
+            board.make_move(mv);
+            let score = -self.negamax(board, depth - 1, ply + 1, -beta, -alpha, &mut child_pv);
+            board.unmake_move();
+
+            if self.stopped {
+                return 0;
+            }
+
+            if score >= beta {
+                self.tt.store(key, depth, Bound::Lower, beta, Some(mv), ply);
+                return beta;
+            }
+
+            if score > alpha {
+                alpha = score;
+                best_move = Some(mv);
+                bound = Bound::Exact;
+
+                pv.clear();
+                pv.push(mv);
+                pv.append(&mut child_pv);
+            }
+        }
+
+        self.tt.store(key, depth, bound, alpha, best_move, ply);
+
+        alpha
+    }
+
+    /// Searches captures and promotions until the position is quiet, so the static
+    /// evaluation is never taken in the middle of an exchange. All evasions are searched
+    /// when in check.
+    fn quiescence(&mut self, board: &mut Board, ply: usize, mut alpha: i32, beta: i32) -> i32 {
+        self.nodes += 1;
+
+        if self.should_stop() {
+            return 0;
+        }
+
+        if ply >= MAX_PLY - 1 {
+            return evaluate(board);
+        }
+
+        let in_check = board.in_check();
+        let mut moves = board.generate_legal_moves();
+
+        if in_check && moves.is_empty() {
+            return -MATE + ply as i32;
+        }
+
+        // Standing pat: the side to move can usually do at least as well as the
+        // static evaluation by not capturing, but not when in check
+        let stand_pat = evaluate(board);
+
+        if !in_check {
+            if stand_pat >= beta {
+                return beta;
+            }
+
+            alpha = alpha.max(stand_pat);
+
+            moves.retain(|mv| mv.is_capture() || mv.is_promotion());
+        }
+
+        order_moves(board, &mut moves, None);
+
+        for mv in moves {
+            if !in_check && !mv.is_promotion() {
+                let captured = board.piece_on(mv.to()).map_or(PIECE_VALUES[5], |p| PIECE_VALUES[p % 6]);
+
+                // Delta pruning, even winning the piece outright can't raise alpha
+                if stand_pat + captured + DELTA_MARGIN < alpha {
+                    continue;
+                }
+
+                if board.see(mv) < 0 {
+                    continue;
+                }
+            }
+
+            board.make_move(mv);
+            let score = -self.quiescence(board, ply + 1, -beta, -alpha);
+            board.unmake_move();
+
+            if self.stopped {
+                return 0;
+            }
+
+            if score >= beta {
+                return beta;
+            }
+
+            alpha = alpha.max(score);
+        }
+
+        alpha
+    }
+}
+
+/// Puts `first` at the front, then captures by most valuable victim, least valuable
+/// attacker, then everything else
+fn order_moves(board: &Board, moves: &mut [Move], first: Option<Move>) {
+    moves.sort_by_cached_key(|&mv| {
+        if Some(mv) == first {
+            return i32::MIN;
+        }
+
+        let mut key = 0;
+
+        if mv.is_capture() {
+            let victim = board.piece_on(mv.to()).map_or(PIECE_VALUES[5], |p| PIECE_VALUES[p % 6]);
+            let attacker = board.piece_on(mv.from()).map_or(0, |p| PIECE_VALUES[p % 6]);
+
+            key -= 10 * victim - attacker / 10;
+        }
+
+        if let Some(piece) = mv.promotion_piece() {
+            key -= PIECE_VALUES[piece];
+        }
+
+        key
+    });
+}
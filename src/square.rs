@@ -2,7 +2,7 @@ use std::{str::FromStr, fmt::Display};
 
 use crate::error::ChessError;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Square {
     A1 = 0, B1, C1, D1, E1, F1, G1, H1,
     A2, B2, C2, D2, E2, F2, G2, H2,
@@ -15,12 +15,12 @@ pub enum Square {
 }
 
 impl Square {
-    pub fn to_u8(&self) -> u8 {
-        *self as u8
+    pub fn to_u8(self) -> u8 {
+        self as u8
     }
 
-    pub fn to_u16(&self) -> u16 {
-        *self as u16
+    pub fn to_u16(self) -> u16 {
+        self as u16
     }
 
     pub fn from_u16(n: u16) -> Result<Self, ChessError> {
@@ -94,11 +94,11 @@ impl Square {
         Ok(Self::from_file_rank(file, rank))
     }
 
-    pub fn to_algebraic(&self) -> String {
+    pub fn to_algebraic(self) -> String {
         let file = self.to_u8() % 8;
         let rank = self.to_u8() / 8;
 
-        format!("{}{}", (file + 'a' as u8) as char, (rank + '1' as u8) as char)
+        format!("{}{}", (file + b'a') as char, (rank + b'1') as char)
     }
 }
 
@@ -0,0 +1,171 @@
+use crate::r#move::Move;
+use crate::search::MATE_BOUND;
+
+pub const DEFAULT_HASH_MB: usize = 16;
+pub const MAX_HASH_MB: usize = 4096;
+
+/// How a stored score relates to the true score of the position
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    Exact,
+    /// The search failed high, the true score is at least this
+    Lower,
+    /// The search failed low, the true score is at most this
+    Upper,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TtEntry {
+    key: u64,
+    /// Raw move, 0 for none
+    best_move: u16,
+    score: i16,
+    depth: u8,
+    bound: Bound,
+    age: u8,
+}
+
+impl TtEntry {
+    const EMPTY: TtEntry = TtEntry { key: 0, best_move: 0, score: 0, depth: 0, bound: Bound::Upper, age: 0 };
+
+    pub fn best_move(&self) -> Option<Move> {
+        match self.best_move {
+            0 => None,
+            raw => Move::from_raw(raw).ok(),
+        }
+    }
+
+    pub fn depth(&self) -> u32 {
+        self.depth as u32
+    }
+
+    pub fn bound(&self) -> Bound {
+        self.bound
+    }
+
+    /// The stored score with mate scores made relative to the root again, `ply` being
+    /// the distance from the root of the position probed
+    pub fn score(&self, ply: usize) -> i32 {
+        match self.score as i32 {
+            score if score >= MATE_BOUND => score - ply as i32,
+            score if score <= -MATE_BOUND => score + ply as i32,
+            score => score,
+        }
+    }
+}
+
+/// Transposition table, a fixed size hash table of search results indexed by the
+/// position's Zobrist key
+///
+/// Entries are replaced when they're from an older search or the new result was
+/// searched at least as deep.
+pub struct TranspositionTable {
+    entries: Vec<TtEntry>,
+    age: u8,
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        Self::new(DEFAULT_HASH_MB)
+    }
+}
+
+impl TranspositionTable {
+    /// A table taking up `mb` megabytes, at least one entry
+    pub fn new(mb: usize) -> Self {
+        let mut tt = TranspositionTable { entries: Vec::new(), age: 0 };
+        tt.resize(mb);
+        tt
+    }
+
+    /// Changes the size to `mb` megabytes, clearing the table
+    pub fn resize(&mut self, mb: usize) {
+        let len = (mb.min(MAX_HASH_MB) * 1024 * 1024 / std::mem::size_of::<TtEntry>()).max(1);
+
+        self.entries = vec![TtEntry::EMPTY; len];
+        self.age = 0;
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.fill(TtEntry::EMPTY);
+        self.age = 0;
+    }
+
+    /// Size in megabytes
+    pub fn size_mb(&self) -> usize {
+        self.entries.len() * std::mem::size_of::<TtEntry>() / (1024 * 1024)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Marks the start of a new search, so entries from earlier searches are replaced
+    /// first
+    pub fn new_search(&mut self) {
+        self.age = self.age.wrapping_add(1);
+    }
+
+    /// Permille of entries used by the current search, as UCI's `hashfull` reports it
+    pub fn hashfull(&self) -> usize {
+        let sample = self.entries.len().min(1000);
+
+        let used = self.entries[..sample].iter()
+            .filter(|entry| entry.key != 0 && entry.age == self.age)
+            .count();
+
+        used * 1000 / sample
+    }
+
+    pub fn probe(&self, key: u64) -> Option<&TtEntry> {
+        let entry = &self.entries[self.index(key)];
+
+        (entry.key == key && key != 0).then_some(entry)
+    }
+
+    /// Stores a search result. `ply` is the distance from the root, mate scores are
+    /// stored relative to this position so they stay right wherever it's reached from.
+    pub fn store(&mut self, key: u64, depth: u32, bound: Bound, score: i32, best_move: Option<Move>, ply: usize) {
+        let age = self.age;
+        let index = self.index(key);
+        let entry = &mut self.entries[index];
+
+        let replace = entry.key == key || entry.age != age || depth >= entry.depth as u32;
+
+        if !replace {
+            return;
+        }
+
+        let score = match score {
+            score if score >= MATE_BOUND => score + ply as i32,
+            score if score <= -MATE_BOUND => score - ply as i32,
+            score => score,
+        };
+
+        // Keep the old move rather than forget it when this search didn't find one
+        let best_move = match best_move {
+            Some(mv) => mv.to_u16(),
+            None if entry.key == key => entry.best_move,
+            None => 0,
+        };
+
+        *entry = TtEntry {
+            key,
+            best_move,
+            score: score as i16,
+            depth: depth.min(u8::MAX as u32) as u8,
+            bound,
+            age,
+        };
+    }
+
+    fn index(&self, key: u64) -> usize {
+        // Maps the key onto the table without needing a power of two size
+        ((key as u128 * self.entries.len() as u128) >> 64) as usize
+    }
+}
@@ -0,0 +1,290 @@
+use std::io::{BufRead, Write};
+use std::time::Duration;
+
+use crate::board::{Board, WHITE};
+use crate::engine::Engine;
+use crate::search::{allocate_time, SearchLimits, SearchResult};
+use crate::tt::{DEFAULT_HASH_MB, MAX_HASH_MB};
+
+/// The limits given to a `go` command
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GoParams {
+    pub depth: Option<u32>,
+    pub nodes: Option<u64>,
+    pub movetime: Option<Duration>,
+    pub wtime: Option<Duration>,
+    pub btime: Option<Duration>,
+    pub winc: Option<Duration>,
+    pub binc: Option<Duration>,
+    pub movestogo: Option<u32>,
+    pub infinite: bool,
+    pub ponder: bool,
+}
+
+impl GoParams {
+    /// Parses the arguments following `go`, ignoring any it doesn't know
+    pub fn parse(args: &[&str]) -> GoParams {
+        let mut params = GoParams::default();
+        let mut args = args.iter();
+
+        while let Some(&arg) = args.next() {
+            let mut value = || args.next().and_then(|v| v.parse::<u64>().ok());
+
+            match arg {
+                "depth" => params.depth = value().map(|v| v as u32),
+                "nodes" => params.nodes = value(),
+                "movetime" => params.movetime = value().map(Duration::from_millis),
+                "wtime" => params.wtime = value().map(Duration::from_millis),
+                "btime" => params.btime = value().map(Duration::from_millis),
+                "winc" => params.winc = value().map(Duration::from_millis),
+                "binc" => params.binc = value().map(Duration::from_millis),
+                "movestogo" => params.movestogo = value().map(|v| v as u32),
+                "infinite" => params.infinite = true,
+                "ponder" => params.ponder = true,
+                _ => {},
+            }
+        }
+
+        params
+    }
+
+    /// The search limits for `side` to move, sharing out its clock when there's no fixed
+    /// depth, node count or move time
+    pub fn limits(&self, side: usize) -> SearchLimits {
+        let (time_left, increment) = match side {
+            WHITE => (self.wtime, self.winc),
+            _ => (self.btime, self.binc),
+        };
+
+        let time = match (self.movetime, time_left) {
+            _ if self.infinite => None,
+            (Some(movetime), _) => Some(movetime),
+            (None, Some(time_left)) => Some(allocate_time(time_left, increment.unwrap_or_default(), self.movestogo)),
+            (None, None) => None,
+        };
+
+        SearchLimits {
+            depth: self.depth.filter(|_| !self.infinite),
+            nodes: self.nodes.filter(|_| !self.infinite),
+            time,
+        }
+    }
+}
+
+/// Universal Chess Interface front-end
+///
+/// http://wbec-ridderkerk.nl/html/UCIProtocol.html
+///
+/// Searches run on a background thread so `stop`, `ponderhit` and `isready` are answered
+/// while thinking. Output from both threads goes to the same writer.
+pub struct Uci<W: Write + Send + 'static> {
+    board: Board,
+    engine: Engine<W, ()>,
+
+    /// Time to think once a ponder search becomes a normal one
+    ponder_time: Option<Duration>,
+}
+
+impl<W: Write + Send + 'static> Uci<W> {
+    pub fn new(out: W) -> Self {
+        Uci {
+            board: Board::startpos(),
+            engine: Engine::new(out),
+            ponder_time: None,
+        }
+    }
+
+    /// Reads commands from `input` until `quit` or the end of input. A search with a limit
+    /// still running at the end of input is finished, `go infinite` and `go ponder` are
+    /// stopped.
+    pub fn run<R: BufRead>(&mut self, input: R) -> std::io::Result<()> {
+        for line in input.lines() {
+            if !self.handle(&line?)? {
+                self.engine.stop();
+                return Ok(());
+            }
+        }
+
+        self.engine.wait();
+        self.engine.stop();
+
+        Ok(())
+    }
+
+    /// Handles a single command, returning false once the engine should quit
+    pub fn handle(&mut self, line: &str) -> std::io::Result<bool> {
+        let cmd = line.split_whitespace().collect::<Vec<_>>();
+
+        match cmd.as_slice() {
+            ["uci"] => {
+                let mut out = self.engine.out();
+                writeln!(out, "id name jesschess {}", crate::version())?;
+                writeln!(out, "id author jesschess developers")?;
+                writeln!(out, "option name Hash type spin default {} min 1 max {}", DEFAULT_HASH_MB, MAX_HASH_MB)?;
+                writeln!(out, "option name OwnBook type check default false")?;
+                writeln!(out, "option name Book File type string default <empty>")?;
+                writeln!(out, "uciok")?;
+            },
+            ["isready"] => writeln!(self.engine.out(), "readyok")?,
+            ["ucinewgame"] => {
+                self.engine.stop();
+                self.board = Board::startpos();
+                self.engine.tt().clear();
+            },
+            ["position", args @ ..] => {
+                self.engine.stop();
+
+                if let Err(e) = self.position(args) {
+                    writeln!(self.engine.out(), "info string {}", e)?;
+                }
+            },
+            ["go", args @ ..] => self.go(&GoParams::parse(args)),
+            ["stop"] => {
+                self.engine.stop();
+            },
+            ["ponderhit"] => self.engine.release(self.ponder_time),
+            ["setoption", args @ ..] => self.setoption(args)?,
+            ["debug", ..] | [] => {},
+            ["quit"] => return Ok(false),
+            _ => writeln!(self.engine.out(), "info string Unknown command: {}", line.trim())?,
+        }
+
+        self.engine.out().flush()?;
+
+        Ok(true)
+    }
+
+    /// `setoption name <id> [value <x>]`
+    fn setoption(&mut self, args: &[&str]) -> std::io::Result<()> {
+        let (name, value) = option_name_value(args);
+
+        match name.to_lowercase().as_str() {
+            "hash" => match value.parse::<usize>() {
+                Ok(mb) if (1..=MAX_HASH_MB).contains(&mb) => {
+                    self.engine.stop();
+                    self.engine.tt().resize(mb);
+                },
+                _ => writeln!(self.engine.out(), "info string Invalid Hash value: {}", value)?,
+            },
+            "ownbook" => self.engine.own_book = value == "true",
+            "book file" => {
+                let path = if value == "<empty>" { "" } else { &value };
+
+                if let Err(e) = self.engine.set_book(path) {
+                    writeln!(self.engine.out(), "info string Could not open book {}: {}", value, e)?;
+                }
+            },
+            _ => writeln!(self.engine.out(), "info string Unknown option: {}", name)?,
+        }
+
+        Ok(())
+    }
+
+    /// `position [startpos | fen <fen>] [moves <move>...]`
+    fn position(&mut self, args: &[&str]) -> Result<(), crate::error::ChessError> {
+        let moves_at = args.iter().position(|&arg| arg == "moves").unwrap_or(args.len());
+        let (setup, moves) = args.split_at(moves_at);
+
+        let mut board = match setup {
+            ["startpos"] => Board::startpos(),
+            ["fen", fen @ ..] => Board::from_fen(&fen.join(" "))?,
+            _ => return Err(crate::error::ChessError::InvalidFen {
+                msg: format!("Invalid position: {}", setup.join(" ")) }),
+        };
+
+        for uci in moves.iter().skip(1) {
+            let mv = board.parse_uci_move(uci)?;
+            board.make_move(mv);
+        }
+
+        self.board = board;
+
+        Ok(())
+    }
+
+    fn go(&mut self, params: &GoParams) {
+        self.engine.stop();
+
+        // Book moves are played straight away, there's nothing to think about
+        if !params.infinite && !params.ponder {
+            if let Some(mv) = self.engine.book_move(&self.board) {
+                let _ = writeln!(self.engine.out(), "bestmove {}", mv.to_algebraic());
+                return;
+            }
+        }
+
+        let mut limits = params.limits(self.board.side_to_move);
+
+        // Pondering thinks on the opponent's time, the clock only starts on ponderhit
+        self.ponder_time = limits.time.filter(|_| params.ponder);
+        if params.ponder {
+            limits.time = None;
+        }
+
+        self.engine.start(
+            &self.board,
+            limits,
+            params.infinite || params.ponder,
+            |out, result, elapsed| {
+                let _ = write_info(out, result, elapsed);
+            },
+            |out, _, result| write_bestmove(out, &result).ok(),
+        );
+    }
+}
+
+impl<W: Write + Send + 'static> Drop for Uci<W> {
+    fn drop(&mut self) {
+        self.engine.stop();
+    }
+}
+
+/// The option name and value from the arguments of `setoption name <id> [value <x>]`,
+/// both may contain spaces
+fn option_name_value(args: &[&str]) -> (String, String) {
+    let name = args.iter()
+        .skip_while(|&&arg| arg != "name")
+        .skip(1)
+        .take_while(|&&arg| arg != "value")
+        .copied()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let value = args.iter()
+        .skip_while(|&&arg| arg != "value")
+        .skip(1)
+        .copied()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    (name, value)
+}
+
+fn write_info<W: Write>(out: &mut W, result: &SearchResult, elapsed: Duration) -> std::io::Result<()> {
+    let score = match result.mate_in() {
+        Some(moves) => format!("mate {}", moves),
+        None => format!("cp {}", result.score),
+    };
+
+    let nps = (result.nodes as f64 / elapsed.as_secs_f64().max(0.001)) as u64;
+
+    let pv = result.pv.iter()
+        .map(|mv| mv.to_algebraic())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    writeln!(out, "info depth {} score {} nodes {} nps {} hashfull {} time {} pv {}",
+        result.depth, score, result.nodes, nps, result.hashfull, elapsed.as_millis(), pv)?;
+    out.flush()
+}
+
+fn write_bestmove<W: Write>(out: &mut W, result: &SearchResult) -> std::io::Result<()> {
+    match (result.best_move, result.pv.get(1)) {
+        (Some(mv), Some(ponder)) => writeln!(out, "bestmove {} ponder {}", mv.to_algebraic(), ponder.to_algebraic())?,
+        (Some(mv), None) => writeln!(out, "bestmove {}", mv.to_algebraic())?,
+        // UCI has no way to say there are no moves, a null move is the convention
+        (None, _) => writeln!(out, "bestmove 0000")?,
+    }
+
+    out.flush()
+}
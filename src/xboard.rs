@@ -0,0 +1,385 @@
+use std::io::{BufRead, Write};
+use std::time::Duration;
+
+use crate::board::{Board, BLACK};
+use crate::engine::Engine;
+use crate::r#move::Move;
+use crate::search::{allocate_time, SearchLimits, SearchResult};
+
+/// Time per move when there's no time control
+const DEFAULT_MOVE_TIME: Duration = Duration::from_secs(1);
+
+/// Time control set by `level`, `st` and `sd`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimeControl {
+    /// Moves per time control period, 0 for the whole game
+    pub moves_per_session: u32,
+    pub base: Duration,
+    pub increment: Duration,
+
+    /// Fixed time per move from `st`
+    pub move_time: Option<Duration>,
+    /// Depth limit from `sd`
+    pub depth: Option<u32>,
+}
+
+/// Chess Engine Communication Protocol (XBoard/WinBoard) front-end
+///
+/// https://www.gnu.org/software/xboard/engine-intf.html
+///
+/// Searches run on a background thread, as with `Uci`, so `?`, `ping` and the clock
+/// updates are answered while thinking and anything else interrupts the search. Output
+/// from both threads goes to the same writer.
+pub struct XBoard<W: Write + Send + 'static> {
+    board: Board,
+    /// Searches give back the move they played
+    engine: Engine<W, Move>,
+
+    /// The side the engine plays, `None` in force mode
+    engine_side: Option<usize>,
+    analyzing: bool,
+    post: bool,
+
+    pub time_control: TimeControl,
+    /// Engine and opponent clocks from `time` and `otim`
+    pub time_left: Duration,
+    pub opponent_time_left: Duration,
+}
+
+impl<W: Write + Send + 'static> XBoard<W> {
+    pub fn new(out: W) -> Self {
+        XBoard {
+            board: Board::startpos(),
+            engine: Engine::new(out),
+            engine_side: Some(BLACK),
+            analyzing: false,
+            post: false,
+            time_control: TimeControl::default(),
+            time_left: Duration::ZERO,
+            opponent_time_left: Duration::ZERO,
+        }
+    }
+
+    /// Reads commands from `input` until `quit` or the end of input. A move being
+    /// thought about at the end of input is still played, analysis is stopped.
+    pub fn run<R: BufRead>(&mut self, input: R) -> std::io::Result<()> {
+        let mut quit = false;
+
+        for line in input.lines() {
+            if !self.handle(&line?)? {
+                quit = true;
+                break;
+            }
+        }
+
+        if !quit {
+            self.wait_for_move();
+        }
+
+        self.stop_search();
+
+        Ok(())
+    }
+
+    /// Handles a single command, returning false once the engine should quit
+    pub fn handle(&mut self, line: &str) -> std::io::Result<bool> {
+        let cmd = line.split_whitespace().collect::<Vec<_>>();
+
+        // These leave a search running, everything else stops it first
+        match cmd.as_slice() {
+            ["?"] => self.move_now(),
+            ["ping", n] => {
+                self.wait_for_move();
+                writeln!(self.engine.out(), "pong {}", n)?;
+            },
+            ["post"] => self.post = true,
+            ["nopost"] => self.post = false,
+            ["time", centis] => self.time_left = parse_centis(centis),
+            ["otim", centis] => self.opponent_time_left = parse_centis(centis),
+            ["accepted" | "rejected", ..]
+            | ["random" | "easy" | "hard" | "computer" | "." | "hint" | "bk"]
+            | ["name" | "rating" | "ics" | "variant" | "cores", ..]
+            | [] => {},
+            ["quit"] => return Ok(false),
+            _ => {
+                self.stop_search();
+                self.command(line, &cmd)?;
+
+                // Analysis carries on in whatever position the command left
+                if self.analyzing && !self.engine.is_searching() {
+                    self.start_search();
+                }
+            },
+        }
+
+        self.engine.out().flush()?;
+
+        Ok(true)
+    }
+
+    fn command(&mut self, line: &str, cmd: &[&str]) -> std::io::Result<()> {
+        match cmd {
+            ["xboard"] => {},
+            ["protover", _] => {
+                let mut out = self.engine.out();
+                writeln!(out, "feature myname=\"jesschess {}\" setboard=1 usermove=1 ping=1 \
+                    playother=1 san=0 colors=0 analyze=1 memory=1 sigint=0 sigterm=0", crate::version())?;
+                writeln!(out, "feature option=\"OwnBook -check 0\" option=\"Book File -file \"")?;
+                writeln!(out, "feature done=1")?;
+            },
+            ["new"] => {
+                self.board = Board::startpos();
+                self.engine.tt().clear();
+                self.engine_side = Some(BLACK);
+                self.time_control.move_time = None;
+                self.time_control.depth = None;
+            },
+            ["setboard", fen @ ..] => {
+                match Board::from_fen(&fen.join(" ")) {
+                    Ok(board) => self.board = board,
+                    Err(e) => writeln!(self.engine.out(), "tellusererror Illegal position: {}", e)?,
+                }
+            },
+            ["usermove", mv] => self.usermove(mv)?,
+            ["go"] => {
+                self.engine_side = Some(self.board.side_to_move);
+                self.think()?;
+            },
+            ["playother"] => self.engine_side = Some(self.board.side_to_move ^ 1),
+            ["force"] => self.engine_side = None,
+            ["undo"] => {
+                self.board.unmake_move();
+            },
+            ["remove"] => {
+                self.board.unmake_move();
+                self.board.unmake_move();
+            },
+            ["level", mps, base, inc] => {
+                let (Ok(mps), Some(base), Ok(inc)) = (mps.parse(), parse_base(base), inc.parse::<f64>()) else {
+                    return writeln!(self.engine.out(), "Error (bad arguments): {}", line.trim());
+                };
+
+                self.time_control.moves_per_session = mps;
+                self.time_control.base = base;
+                self.time_control.increment = Duration::from_secs_f64(inc);
+                self.time_control.move_time = None;
+            },
+            ["st", secs] => match secs.parse::<f64>() {
+                Ok(secs) => self.time_control.move_time = Some(Duration::from_secs_f64(secs)),
+                Err(_) => writeln!(self.engine.out(), "Error (bad arguments): {}", line.trim())?,
+            },
+            ["sd", depth] => match depth.parse() {
+                Ok(depth) => self.time_control.depth = Some(depth),
+                Err(_) => writeln!(self.engine.out(), "Error (bad arguments): {}", line.trim())?,
+            },
+            ["memory", mb] => match mb.parse() {
+                Ok(mb) => self.engine.tt().resize(mb),
+                Err(_) => writeln!(self.engine.out(), "Error (bad arguments): {}", line.trim())?,
+            },
+            ["analyze"] => {
+                self.engine_side = None;
+                self.analyzing = true;
+            },
+            ["exit"] => self.analyzing = false,
+            ["result", ..] => self.engine_side = None,
+            ["option", ..] => self.option(line)?,
+            _ => writeln!(self.engine.out(), "Error (unknown command): {}", line.trim())?,
+        }
+
+        Ok(())
+    }
+
+    fn usermove(&mut self, text: &str) -> std::io::Result<()> {
+        let mv = match self.board.parse_uci_move(text) {
+            Ok(mv) => mv,
+            Err(_) => return writeln!(self.engine.out(), "Illegal move: {}", text),
+        };
+
+        self.board.make_move(mv);
+
+        if self.report_result()? {
+            return Ok(());
+        }
+
+        if self.engine_side == Some(self.board.side_to_move) {
+            self.think()?;
+        }
+
+        Ok(())
+    }
+
+    /// `option <name>=<value>` for the options announced with `feature option`
+    fn option(&mut self, line: &str) -> std::io::Result<()> {
+        let option = line.trim().trim_start_matches("option").trim_start();
+        let (name, value) = option.split_once('=').unwrap_or((option, ""));
+
+        match name {
+            "OwnBook" => self.engine.own_book = value == "1",
+            "Book File" => {
+                if let Err(e) = self.engine.set_book(value) {
+                    writeln!(self.engine.out(), "tellusererror Could not open book {}: {}", value, e)?;
+                }
+            },
+            _ => writeln!(self.engine.out(), "Error (unknown option): {}", name)?,
+        }
+
+        Ok(())
+    }
+
+    /// Plays a move for the engine straight from the book when there is one, otherwise
+    /// starts thinking about one
+    fn think(&mut self) -> std::io::Result<()> {
+        if self.board.outcome().is_some() {
+            return self.report_result().map(|_| ());
+        }
+
+        let Some(mv) = self.engine.book_move(&self.board) else {
+            self.start_search();
+            return Ok(());
+        };
+
+        self.board.make_move(mv);
+        writeln!(self.engine.out(), "move {}", mv.to_algebraic())?;
+        self.report_result()?;
+
+        Ok(())
+    }
+
+    /// Searches the current position on a background thread: analysis until it's
+    /// stopped, or thinking about a move which is played when the search ends
+    fn start_search(&mut self) {
+        let post = self.post || self.analyzing;
+
+        self.engine.start(
+            &self.board,
+            self.limits(),
+            self.analyzing,
+            move |out, result, elapsed| {
+                if post {
+                    let _ = write_thinking(out, result, elapsed);
+                }
+            },
+            |out, mut board, result| {
+                let mv = result.best_move?;
+                board.make_move(mv);
+
+                let _ = writeln!(out, "move {}", mv.to_algebraic());
+                let _ = write_result(out, &board);
+
+                Some(mv)
+            },
+        );
+    }
+
+    /// `?`, stops thinking and plays the best move found so far
+    fn move_now(&mut self) {
+        if !self.engine.is_held() {
+            let mv = self.engine.stop();
+            self.played(mv);
+        }
+    }
+
+    /// Lets a search for the engine's move run to the end
+    fn wait_for_move(&mut self) {
+        let mv = self.engine.wait();
+        self.played(mv);
+    }
+
+    /// Stops any search, without playing its move unless it already has
+    fn stop_search(&mut self) {
+        let mv = self.engine.abandon();
+        self.played(mv);
+    }
+
+    /// Makes the move the search played on the board
+    fn played(&mut self, mv: Option<Move>) {
+        let Some(mv) = mv else { return; };
+
+        self.board.make_move(mv);
+
+        if self.board.outcome().is_some() {
+            self.engine_side = None;
+        }
+    }
+
+    /// Search limits from `sd`, `st` or the clock. Analysis has no time limit, games
+    /// without any time control get a fixed time per move.
+    fn limits(&self) -> SearchLimits {
+        let tc = &self.time_control;
+
+        let clock = if self.time_left.is_zero() { tc.base } else { self.time_left };
+
+        let time = match tc.move_time {
+            _ if self.analyzing => None,
+            Some(move_time) => Some(move_time),
+            None if clock.is_zero() => match tc.depth {
+                Some(_) => None,
+                None => Some(DEFAULT_MOVE_TIME),
+            },
+            None => {
+                // Moves left until the next time control, counting this one
+                let played = self.board.fullmove_counter.saturating_sub(1);
+                let moves_to_go = match tc.moves_per_session {
+                    0 => None,
+                    mps => Some(mps - played % mps),
+                };
+
+                Some(allocate_time(clock, tc.increment, moves_to_go))
+            },
+        };
+
+        SearchLimits {
+            depth: tc.depth,
+            nodes: None,
+            time,
+        }
+    }
+
+    /// Tells the GUI when the game is over, returning whether it is
+    fn report_result(&mut self) -> std::io::Result<bool> {
+        let over = write_result(&mut *self.engine.out(), &self.board)?;
+
+        if over {
+            self.engine_side = None;
+        }
+
+        Ok(over)
+    }
+}
+
+/// Writes the result when the game is over in `board`, returning whether it is
+fn write_result<W: Write>(out: &mut W, board: &Board) -> std::io::Result<bool> {
+    let Some(outcome) = board.outcome() else { return Ok(false); };
+
+    writeln!(out, "{} {{{}}}", outcome.result(), outcome)?;
+
+    Ok(true)
+}
+
+/// Prints a line of thinking output: ply score time(centiseconds) nodes pv
+fn write_thinking<W: Write>(out: &mut W, result: &SearchResult, elapsed: Duration) -> std::io::Result<()> {
+    // Mates are shown as 100000 + moves to mate by convention
+    let score = match result.mate_in() {
+        Some(moves) if moves > 0 => 100_000 + moves,
+        Some(moves) => -100_000 + moves,
+        None => result.score,
+    };
+
+    let pv = result.pv.iter()
+        .map(|mv| mv.to_algebraic())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    writeln!(out, "{} {} {} {} {}", result.depth, score, elapsed.as_millis() / 10, result.nodes, pv)
+}
+
+/// Parses the base time of `level`, given as minutes or minutes:seconds
+fn parse_base(base: &str) -> Option<Duration> {
+    let (mins, secs) = base.split_once(':').unwrap_or((base, "0"));
+
+    Some(Duration::from_secs(mins.parse::<u64>().ok()? * 60 + secs.parse::<u64>().ok()?))
+}
+
+fn parse_centis(centis: &str) -> Duration {
+    Duration::from_millis(centis.parse::<u64>().unwrap_or(0) * 10)
+}
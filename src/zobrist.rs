@@ -0,0 +1,104 @@
+use crate::board::*;
+use crate::movegen::pawn_attacks;
+
+/// Random keys XORed together to hash a position
+///
+/// https://www.chessprogramming.org/Zobrist_Hashing
+pub(crate) struct ZobristKeys {
+    /// Indexed by piece, then square
+    pub pieces: [[u64; 64]; 12],
+    /// XORed in when Black is to move
+    pub black_to_move: u64,
+    /// Indexed by the castling rights bits
+    pub castling: [u64; 16],
+    /// Indexed by the file of the en passant square, see `Board::en_passant_key`
+    pub en_passant: [u64; 8],
+}
+
+pub(crate) const KEYS: ZobristKeys = generate_keys();
+
+/// SplitMix64, fixed seed so hashes are the same from run to run
+const fn next_key(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+const fn generate_keys() -> ZobristKeys {
+    let mut state = 0x6A65_7373_6368_6573;
+
+    let mut keys = ZobristKeys {
+        pieces: [[0; 64]; 12],
+        black_to_move: 0,
+        castling: [0; 16],
+        en_passant: [0; 8],
+    };
+
+    let mut piece = 0;
+    while piece < 12 {
+        let mut sq = 0;
+        while sq < 64 {
+            keys.pieces[piece][sq] = next_key(&mut state);
+            sq += 1;
+        }
+        piece += 1;
+    }
+
+    keys.black_to_move = next_key(&mut state);
+
+    let mut i = 0;
+    while i < 16 {
+        keys.castling[i] = next_key(&mut state);
+        i += 1;
+    }
+
+    let mut i = 0;
+    while i < 8 {
+        keys.en_passant[i] = next_key(&mut state);
+        i += 1;
+    }
+
+    keys
+}
+
+impl Board {
+    /// Zobrist key of the position, computed from scratch
+    pub(crate) fn zobrist_key(&self) -> u64 {
+        let mut key = 0;
+
+        for (piece, bb) in self.bitboards.iter().enumerate() {
+            let mut bits = u64::from(*bb);
+
+            while bits != 0 {
+                key ^= KEYS.pieces[piece][bits.trailing_zeros() as usize];
+                bits &= bits - 1;
+            }
+        }
+
+        if self.side_to_move == BLACK {
+            key ^= KEYS.black_to_move;
+        }
+
+        key ^= KEYS.castling[self.castling_rights as usize & 15];
+
+        key ^ self.en_passant_key()
+    }
+
+    /// Key for the en passant file, only when a pawn of the side to move could capture
+    /// there. After most double pushes none can, and the position repeats one without
+    /// an en passant square.
+    pub(crate) fn en_passant_key(&self) -> u64 {
+        let Some(sq) = self.en_passant else { return 0; };
+
+        let us = self.side_to_move;
+        let pawns = u64::from(self.bitboards[us * 6 + WHITE_PAWN]);
+
+        match pawn_attacks(us ^ 1, sq.to_u8()) & pawns {
+            0 => 0,
+            _ => KEYS.en_passant[sq.to_u8() as usize % 8],
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use jesschess::attacks::*;
+use jesschess::board::{BLACK, WHITE};
+
+fn square_name(sq: usize) -> String {
+    format!("{}{}", (b'a' + (sq % 8) as u8) as char, sq / 8 + 1)
+}
+
+/// Every square reached by stepping once by each of `steps`
+fn leaper_reference(sq: usize, steps: &[(i32, i32)]) -> u64 {
+    let (file, rank) = ((sq % 8) as i32, (sq / 8) as i32);
+
+    steps.iter()
+        .map(|(df, dr)| (file + df, rank + dr))
+        .filter(|(f, r)| (0..8).contains(f) && (0..8).contains(r))
+        .fold(0, |bb, (f, r)| bb | 1 << (r * 8 + f))
+}
+
+/// Walks each ray one square at a time, stopping after the first blocker
+fn slider_reference(sq: usize, occupancy: u64, directions: &[(i32, i32)]) -> u64 {
+    let mut attacks = 0;
+
+    for (df, dr) in directions {
+        let (mut file, mut rank) = ((sq % 8) as i32 + df, (sq / 8) as i32 + dr);
+
+        while (0..8).contains(&file) && (0..8).contains(&rank) {
+            attacks |= 1 << (rank * 8 + file);
+
+            if occupancy & 1 << (rank * 8 + file) != 0 {
+                break;
+            }
+
+            file += df;
+            rank += dr;
+        }
+    }
+
+    attacks
+}
+
+const ROOK: [(i32, i32); 4] = [(0, 1), (0, -1), (1, 0), (-1, 0)];
+const BISHOP: [(i32, i32); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+/// Occupancies from sparse to dense, from a fixed xorshift sequence
+fn occupancies() -> Vec<u64> {
+    let mut state = 0x9E37_79B9_7F4A_7C15u64;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+
+    (0..300)
+        .map(|i| match i % 3 {
+            0 => next() & next() & next(),
+            1 => next() & next(),
+            _ => next(),
+        })
+        .chain([0, !0])
+        .collect()
+}
+
+#[test]
+fn leaper_attacks() {
+    let knight = [(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)];
+    let king = [(0, 1), (1, 1), (1, 0), (1, -1), (0, -1), (-1, -1), (-1, 0), (-1, 1)];
+
+    for sq in 0..64 {
+        let name = square_name(sq);
+
+        assert_eq!(u64::from(knight_attacks(name.parse().unwrap())), leaper_reference(sq, &knight), "{}", name);
+        assert_eq!(u64::from(king_attacks(name.parse().unwrap())), leaper_reference(sq, &king), "{}", name);
+        assert_eq!(u64::from(pawn_attacks(WHITE, name.parse().unwrap())), leaper_reference(sq, &[(-1, 1), (1, 1)]), "{}", name);
+        assert_eq!(u64::from(pawn_attacks(BLACK, name.parse().unwrap())), leaper_reference(sq, &[(-1, -1), (1, -1)]), "{}", name);
+    }
+}
+
+#[test]
+fn slider_attacks_match_ray_walking() {
+    let occupancies = occupancies();
+
+    for sq in 0..64 {
+        let name = square_name(sq);
+
+        for &occupancy in &occupancies {
+            let rook = slider_reference(sq, occupancy, &ROOK);
+            let bishop = slider_reference(sq, occupancy, &BISHOP);
+
+            assert_eq!(u64::from(rook_attacks(name.parse().unwrap(), occupancy.into())), rook, "{} {:#x}", name, occupancy);
+            assert_eq!(u64::from(bishop_attacks(name.parse().unwrap(), occupancy.into())), bishop, "{} {:#x}", name, occupancy);
+            assert_eq!(u64::from(queen_attacks(name.parse().unwrap(), occupancy.into())), rook | bishop, "{} {:#x}", name, occupancy);
+        }
+    }
+}
+
+#[test]
+fn slider_attacks_ignore_the_piece_itself_and_edges() {
+    // A rook on d4 sees the same whether or not d4 and the edges are occupied
+    let d4 = 1u64 << 27;
+    let edges = 0xFF81_8181_8181_81FFu64;
+
+    assert_eq!(u64::from(rook_attacks("d4".parse().unwrap(), d4.into())), slider_reference(27, 0, &ROOK));
+    assert_eq!(u64::from(rook_attacks("d4".parse().unwrap(), edges.into())), slider_reference(27, 0, &ROOK));
+    assert_eq!(u64::from(bishop_attacks("d4".parse().unwrap(), (d4 | edges).into())), slider_reference(27, 0, &BISHOP));
+}
+
+// `cargo test --features pext` runs everything here against the pext backend
+#[test]
+fn sliding_backends_agree() {
+    let magic = SliderTables::new(SlidingBackend::Magic).unwrap();
+
+    // Only there with the `pext` feature on a CPU with BMI2
+    let Some(pext) = SliderTables::new(SlidingBackend::Pext) else {
+        assert_eq!(sliding_backend(), SlidingBackend::Magic);
+        return;
+    };
+
+    assert_eq!(sliding_backend(), SlidingBackend::Pext);
+
+    let occupancies = occupancies();
+
+    for sq in 0..64 {
+        let square = square_name(sq).parse().unwrap();
+
+        for &occupancy in &occupancies {
+            assert_eq!(pext.rook_attacks(square, occupancy.into()), magic.rook_attacks(square, occupancy.into()), "{} {:#x}", square, occupancy);
+            assert_eq!(pext.bishop_attacks(square, occupancy.into()), magic.bishop_attacks(square, occupancy.into()), "{} {:#x}", square, occupancy);
+        }
+    }
+}
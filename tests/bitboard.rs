@@ -0,0 +1,102 @@
+use jesschess::bitboard::*;
+use jesschess::square::Square;
+
+fn squares(names: &[&str]) -> Bitboard {
+    names.iter().map(|name| name.parse::<Square>().unwrap()).collect()
+}
+
+#[test]
+fn operators() {
+    let a = squares(&["a1", "b2", "c3"]);
+    let b = squares(&["b2", "c3", "d4"]);
+
+    assert_eq!(a & b, squares(&["b2", "c3"]));
+    assert_eq!(a | b, squares(&["a1", "b2", "c3", "d4"]));
+    assert_eq!(a ^ b, squares(&["a1", "d4"]));
+    assert_eq!(a - b, squares(&["a1"]));
+    assert_eq!(!a & a, EMPTY);
+    assert_eq!(!EMPTY, FULL);
+
+    let mut c = a;
+    c &= b;
+    assert_eq!(c, squares(&["b2", "c3"]));
+    c -= squares(&["c3"]);
+    assert_eq!(c, squares(&["b2"]));
+
+    assert!(c.is_subset_of(a));
+    assert!(!a.is_subset_of(b));
+}
+
+#[test]
+fn shifts_drop_squares_off_the_edge() {
+    let corners = squares(&["a1", "h1", "a8", "h8"]);
+
+    assert_eq!(corners.north(), squares(&["a2", "h2"]));
+    assert_eq!(corners.south(), squares(&["a7", "h7"]));
+    assert_eq!(corners.east(), squares(&["b1", "b8"]));
+    assert_eq!(corners.west(), squares(&["g1", "g8"]));
+    assert_eq!(corners.north_east(), squares(&["b2"]));
+    assert_eq!(corners.north_west(), squares(&["g2"]));
+    assert_eq!(corners.south_east(), squares(&["b7"]));
+    assert_eq!(corners.south_west(), squares(&["g7"]));
+
+    assert_eq!(FILE_H.east(), EMPTY);
+    assert_eq!(FILE_A.west(), EMPTY);
+    assert_eq!(FILE_A.east(), FILE_B);
+    assert_eq!(RANK_1.north(), RANK_2);
+}
+
+#[test]
+fn lsb_msb_and_iteration() {
+    let mut bb = squares(&["c2", "a5", "h8"]);
+
+    assert_eq!(bb.lsb(), Some(Square::C2));
+    assert_eq!(bb.msb(), Some(Square::H8));
+    assert_eq!(bb.iter().collect::<Vec<_>>(), [Square::C2, Square::A5, Square::H8]);
+    assert_eq!(bb.into_iter().len(), 3);
+
+    assert_eq!(bb.pop_lsb(), Some(Square::C2));
+    assert_eq!(bb, squares(&["a5", "h8"]));
+    assert_eq!(bb.pop_lsb(), Some(Square::A5));
+    assert_eq!(bb.pop_lsb(), Some(Square::H8));
+    assert_eq!(bb.pop_lsb(), None);
+    assert_eq!(EMPTY.msb(), None);
+
+    assert_eq!(FULL.into_iter().count(), 64);
+}
+
+#[test]
+fn subsets() {
+    let set = squares(&["a1", "d4", "h8"]);
+    let subsets: Vec<_> = set.subsets().collect();
+
+    assert_eq!(subsets.len(), 8);
+    assert_eq!(subsets[0], EMPTY);
+    assert_eq!(subsets[7], set);
+    assert!(subsets.iter().all(|subset| subset.is_subset_of(set)));
+
+    for (i, a) in subsets.iter().enumerate() {
+        assert!(subsets[i + 1..].iter().all(|b| a != b));
+    }
+
+    assert_eq!(EMPTY.subsets().collect::<Vec<_>>(), [EMPTY]);
+}
+
+#[test]
+fn constants() {
+    assert_eq!(FILES.iter().fold(EMPTY, |all, &file| all | file), FULL);
+    assert_eq!(RANKS.iter().fold(EMPTY, |all, &rank| all | rank), FULL);
+    assert_eq!(FILE_E, squares(&["e1", "e2", "e3", "e4", "e5", "e6", "e7", "e8"]));
+    assert_eq!(RANK_7, squares(&["a7", "b7", "c7", "d7", "e7", "f7", "g7", "h7"]));
+
+    assert_eq!(DIAGONAL, squares(&["a1", "b2", "c3", "d4", "e5", "f6", "g7", "h8"]));
+    assert_eq!(ANTI_DIAGONAL, squares(&["a8", "b7", "c6", "d5", "e4", "f3", "g2", "h1"]));
+    assert_eq!(Bitboard::diagonal(Square::C1), squares(&["c1", "d2", "e3", "f4", "g5", "h6"]));
+    assert_eq!(Bitboard::diagonal(Square::A7), squares(&["a7", "b8"]));
+    assert_eq!(Bitboard::anti_diagonal(Square::C1), squares(&["a3", "b2", "c1"]));
+    assert_eq!(Bitboard::anti_diagonal(Square::H7), squares(&["g8", "h7"]));
+
+    assert!(LIGHT_SQUARES.is_occ(Square::H1));
+    assert!(DARK_SQUARES.is_occ(Square::A1));
+    assert_eq!(LIGHT_SQUARES | DARK_SQUARES, FULL);
+}
@@ -0,0 +1,298 @@
+use std::io::Cursor;
+
+use jesschess::board::Board;
+use jesschess::book::{decode_move, encode_move, polyglot_key, Book, BookBuilder, BookEntry, Selection};
+use jesschess::uci::Uci;
+use jesschess::xboard::XBoard;
+
+/// Reference keys from the Polyglot book format description
+const KEYS: [(&str, u64); 9] = [
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 0x463b96181691fc9c),
+    ("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1", 0x823c9b50fd114196),
+    ("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2", 0x0756b94461c50fb0),
+    ("rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2", 0x662fafb965db29d4),
+    ("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3", 0x22a48b5a8e47ff78),
+    ("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR b kq - 0 3", 0x652a607ca3f242c1),
+    ("rnbq1bnr/ppp1pkpp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR w - - 0 4", 0x00fdd303c946bdd9),
+    ("rnbqkbnr/p1pppppp/8/8/PpP4P/8/1P1PPPP1/RNBQKBNR b KQkq c3 0 3", 0x3c8123ea7b067637),
+    ("rnbqkbnr/p1pppppp/8/8/P6P/R1p5/1P1PPPP1/1NBQKBNR b Kkq - 0 4", 0x5c3f9b829b279560),
+];
+
+/// Polyglot's encoding of a coordinate move
+fn encode(uci: &str) -> u16 {
+    let square = |s: &[u8]| ((s[1] - b'1') * 8 + (s[0] - b'a')) as u16;
+    let bytes = uci.as_bytes();
+
+    let promotion = match bytes.get(4) {
+        Some(b'n') => 1,
+        Some(b'b') => 2,
+        Some(b'r') => 3,
+        Some(b'q') => 4,
+        _ => 0,
+    };
+
+    promotion << 12 | square(&bytes[0..2]) << 6 | square(&bytes[2..4])
+}
+
+/// A book file holding `moves` with their weights for each position, sorted by key
+fn book_bytes(positions: &[(&str, &[(&str, u16)])]) -> Vec<u8> {
+    let mut entries = Vec::new();
+
+    for &(fen, moves) in positions {
+        let key = polyglot_key(&Board::from_fen(fen).unwrap());
+
+        for &(uci, weight) in moves {
+            entries.push(BookEntry { key, mv: encode(uci), weight, learn: 0 });
+        }
+    }
+
+    entries.sort_by_key(|entry| (entry.key, std::cmp::Reverse(entry.weight)));
+    entries.iter().flat_map(|entry| entry.to_bytes()).collect()
+}
+
+const AFTER_E4: &str = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1";
+
+fn test_book() -> Book<Cursor<Vec<u8>>> {
+    let bytes = book_bytes(&[
+        (jesschess::board::FEN_STARTPOS, &[("e2e4", 10), ("d2d4", 5), ("c2c4", 0)]),
+        (AFTER_E4, &[("c7c5", 3)]),
+        ("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", &[("e1h1", 1)]),
+    ]);
+
+    Book::new(Cursor::new(bytes)).unwrap()
+}
+
+#[test]
+fn polyglot_keys() {
+    for (fen, key) in KEYS {
+        assert_eq!(polyglot_key(&Board::from_fen(fen).unwrap()), key, "{}", fen);
+    }
+}
+
+#[test]
+fn entries_round_trip() {
+    let entry = BookEntry { key: 0x0123456789abcdef, mv: 0x1234, weight: 0xfedc, learn: 0xdeadbeef };
+    let bytes = entry.to_bytes();
+
+    assert_eq!(bytes[0], 0x01);
+    assert_eq!(BookEntry::from_bytes(&bytes), entry);
+}
+
+#[test]
+fn decodes_moves() {
+    let board = Board::from_fen("r3k2r/1P6/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+
+    assert_eq!(decode_move(&board, encode("e1h1")).unwrap().to_algebraic(), "e1g1");
+    assert_eq!(decode_move(&board, encode("e1a1")).unwrap().to_algebraic(), "e1c1");
+    assert!(decode_move(&board, encode("e1a1")).unwrap().is_castle());
+    assert_eq!(decode_move(&board, encode("b7a8n")).unwrap().to_algebraic(), "b7a8n");
+    assert!(decode_move(&board, encode("e1e3")).is_none());
+}
+
+#[test]
+fn looks_up_positions() {
+    let mut book = test_book();
+    assert_eq!(book.len(), 5);
+
+    let moves = book.moves(&Board::startpos()).unwrap();
+    let moves: Vec<_> = moves.iter().map(|&(mv, weight)| (mv.to_algebraic(), weight)).collect();
+    assert_eq!(moves, [("e2e4".to_string(), 10), ("d2d4".to_string(), 5), ("c2c4".to_string(), 0)]);
+
+    let board = Board::from_fen(AFTER_E4).unwrap();
+    assert_eq!(book.pick(&board, Selection::Best).unwrap().unwrap().to_algebraic(), "c7c5");
+
+    let board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    assert!(book.pick(&board, Selection::Best).unwrap().unwrap().is_castle());
+
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(book.pick(&board, Selection::Best).unwrap(), None);
+    assert_eq!(book.pick(&board, Selection::WeightedRandom).unwrap(), None);
+
+    let mut empty = Book::new(Cursor::new(Vec::new())).unwrap();
+    assert!(empty.is_empty());
+    assert_eq!(empty.pick(&Board::startpos(), Selection::Best).unwrap(), None);
+}
+
+#[test]
+fn picks_by_weight() {
+    let mut book = test_book();
+    let board = Board::startpos();
+
+    assert_eq!(book.pick(&board, Selection::Best).unwrap().unwrap().to_algebraic(), "e2e4");
+
+    book.seed(7);
+
+    let mut e4 = 0;
+    let mut d4 = 0;
+
+    for _ in 0..3000 {
+        match book.pick(&board, Selection::WeightedRandom).unwrap().unwrap().to_algebraic().as_str() {
+            "e2e4" => e4 += 1,
+            "d2d4" => d4 += 1,
+            other => panic!("Picked {} with no weight", other),
+        }
+    }
+
+    // Roughly 2:1
+    assert!((1600..2400).contains(&e4), "e4 {} d4 {}", e4, d4);
+}
+
+#[test]
+fn encodes_moves() {
+    let board = Board::from_fen("r3k2r/1P6/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+
+    for uci in ["e1h1", "e1a1", "b7a8n", "b7b8q", "a1a7"] {
+        let mv = decode_move(&board, encode(uci)).unwrap();
+        assert_eq!(encode_move(mv), encode(uci), "{}", uci);
+    }
+}
+
+const GAMES: &str = r#"[Event "One"]
+[White "Alice"]
+[Black "Bob"]
+[WhiteElo "2400"]
+[BlackElo "2100"]
+[Result "1-0"]
+
+1. e4 {best by test} e5 2. Nf3 (2. f4 exf4) Nc6 $1 3. Bb5 1-0
+
+[Event "Two"]
+[White "Bob"]
+[Black "Alice"]
+[WhiteElo "2100"]
+[BlackElo "2400"]
+[Result "1/2-1/2"]
+
+1. e4 c5 ; a comment to the end of the line
+2. Nf3 d6 1/2-1/2
+
+[Event "Three"]
+[White "Carol"]
+[Black "Bob"]
+[Result "0-1"]
+
+1.d4 Nf6 2.c4 e6 {a comment
+over two lines} 0-1
+
+[Event "Unfinished"]
+[Result "*"]
+
+1. e4 *
+"#;
+
+fn weight(entries: &[BookEntry], fen: &str, uci: &str) -> Option<u16> {
+    let board = Board::from_fen(fen).unwrap();
+    let key = polyglot_key(&board);
+    let mv = board.parse_uci_move(uci).unwrap();
+
+    entries.iter()
+        .find(|entry| entry.key == key && entry.mv == encode_move(mv))
+        .map(|entry| entry.weight)
+}
+
+#[test]
+fn builds_books_from_pgn() {
+    let mut builder = BookBuilder::new();
+    assert_eq!(builder.add_pgn(GAMES.as_bytes()).unwrap(), 3);
+
+    let entries = builder.entries();
+    let start = jesschess::board::FEN_STARTPOS;
+
+    // A win and a draw for White, a loss for Black
+    assert_eq!(weight(&entries, start, "e2e4"), Some(3));
+    assert_eq!(weight(&entries, start, "d2d4"), Some(0));
+    assert_eq!(weight(&entries, AFTER_E4, "e7e5"), Some(0));
+    assert_eq!(weight(&entries, AFTER_E4, "c7c5"), Some(1));
+
+    // Variations are skipped
+    assert_eq!(weight(&entries, "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2", "f2f4"), None);
+
+    assert!(entries.windows(2).all(|pair| pair[0].key <= pair[1].key));
+
+    // The written book reads back
+    let mut bytes = Vec::new();
+    builder.write(&mut bytes).unwrap();
+
+    let mut book = Book::new(Cursor::new(bytes)).unwrap();
+    assert_eq!(book.len(), entries.len() as u64);
+    assert_eq!(book.pick(&Board::startpos(), Selection::Best).unwrap().unwrap().to_algebraic(), "e2e4");
+}
+
+#[test]
+fn filters_games() {
+    let start = jesschess::board::FEN_STARTPOS;
+
+    let mut builder = BookBuilder::new();
+    builder.min_games = 2;
+    builder.add_pgn(GAMES.as_bytes()).unwrap();
+    let entries = builder.entries();
+
+    assert_eq!(weight(&entries, start, "e2e4"), Some(3));
+    assert_eq!(weight(&entries, start, "d2d4"), None);
+
+    let mut builder = BookBuilder::new();
+    builder.max_ply = Some(1);
+    builder.add_pgn(GAMES.as_bytes()).unwrap();
+    let entries = builder.entries();
+
+    assert_eq!(weight(&entries, start, "e2e4"), Some(3));
+    assert_eq!(weight(&entries, AFTER_E4, "c7c5"), None);
+
+    // Only Alice's moves
+    let mut builder = BookBuilder::new();
+    builder.player = Some("Alice".to_string());
+    assert_eq!(builder.add_pgn(GAMES.as_bytes()).unwrap(), 2);
+    let entries = builder.entries();
+
+    assert_eq!(weight(&entries, start, "e2e4"), Some(2));
+    assert_eq!(weight(&entries, AFTER_E4, "c7c5"), Some(1));
+    assert_eq!(weight(&entries, AFTER_E4, "e7e5"), None);
+
+    // Unrated players are left out
+    let mut builder = BookBuilder::new();
+    builder.min_elo = Some(2200);
+    assert_eq!(builder.add_pgn(GAMES.as_bytes()).unwrap(), 2);
+    let entries = builder.entries();
+
+    assert_eq!(weight(&entries, start, "e2e4"), Some(2));
+    assert_eq!(weight(&entries, start, "d2d4"), None);
+    assert_eq!(weight(&entries, AFTER_E4, "c7c5"), Some(1));
+}
+
+#[test]
+fn engines_play_from_the_book() {
+    let path = std::env::temp_dir().join(format!("jesschess-book-{}.bin", std::process::id()));
+    std::fs::write(&path, book_bytes(&[(jesschess::board::FEN_STARTPOS, &[("b1c3", 1)])])).unwrap();
+
+    let out = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let mut uci = Uci::new(SharedOutput(out.clone()));
+    let input = format!("setoption name OwnBook value true\nsetoption name Book File value {}\ngo depth 1\n", path.display());
+    uci.run(input.as_bytes()).unwrap();
+    drop(uci);
+
+    let out = String::from_utf8(out.lock().unwrap().clone()).unwrap();
+    assert_eq!(out.trim(), "bestmove b1c3");
+
+    let out = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let mut xboard = XBoard::new(SharedOutput(out.clone()));
+    let input = format!("new\noption OwnBook=1\noption Book File={}\nsd 1\ngo\n", path.display());
+    xboard.run(input.as_bytes()).unwrap();
+    drop(xboard);
+
+    let out = String::from_utf8(out.lock().unwrap().clone()).unwrap();
+    assert_eq!(out.trim(), "move b1c3");
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+struct SharedOutput(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+impl std::io::Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
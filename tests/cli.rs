@@ -0,0 +1,38 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Runs the binary with `input` piped in, returning what it printed
+fn run(input: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_jesschess"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn uci_handshake_comes_first() {
+    let out = run("uci\nquit\n");
+
+    assert!(out.starts_with("id name jesschess"), "{}", out);
+    assert!(out.lines().any(|line| line == "uciok"));
+}
+
+#[test]
+fn repl_has_no_prompt_when_piped() {
+    let out = run("version\n");
+
+    assert_eq!(out, format!("{}\n", jesschess::version()));
+}
+
+#[test]
+fn xboard_features_come_first() {
+    let out = run("xboard\nprotover 2\nquit\n");
+
+    assert!(out.starts_with("feature "), "{}", out);
+}
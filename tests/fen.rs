@@ -0,0 +1,139 @@
+use jesschess::board::{Board, EnPassantMode, FEN_STARTPOS};
+
+const FENS: [&str; 8] = [
+    FEN_STARTPOS,
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+    "4k3/8/8/8/8/8/8/4K2R b K - 17 42",
+    "8/8/8/8/8/8/8/8 w - - 0 1",
+];
+
+fn play(board: &mut Board, moves: &[&str]) {
+    for text in moves {
+        let mv = board.generate_legal_moves()
+            .into_iter()
+            .find(|mv| mv.to_algebraic() == *text)
+            .unwrap_or_else(|| panic!("Illegal move {}", text));
+
+        board.make_move(mv);
+    }
+}
+
+#[test]
+fn round_trip() {
+    for fen in FENS {
+        assert_eq!(Board::from_fen(fen).unwrap().to_fen(), fen);
+    }
+}
+
+#[test]
+fn round_trip_after_moves() {
+    let mut board = Board::startpos();
+
+    play(&mut board, &["e2e4", "c7c5", "g1f3", "d7d6", "f1b5", "b8d7", "e1g1", "a7a6"]);
+    assert_eq!(board.to_fen(), "r1bqkbnr/1p1npppp/p2p4/1Bp5/4P3/5N2/PPPP1PPP/RNBQ1RK1 w kq - 0 5");
+
+    let fen = board.to_fen();
+    assert_eq!(Board::from_fen(&fen).unwrap().to_fen(), fen);
+}
+
+#[test]
+fn replay_matches_final_position() {
+    let mut board = Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+
+    play(&mut board, &["e2e4", "d7d5", "e4e5", "f7f5", "e5f6", "g8h6", "f6g7", "e8f7", "g7h8q", "b8c6"]);
+    assert_eq!(board.to_fen(), "r1bq1b1Q/ppp1pk1p/2n4n/3p4/8/8/PPPP1PPP/RNBQKBNR w KQ - 1 6");
+
+    while board.unmake_move().is_some() {}
+    assert_eq!(board.to_fen(), FEN_STARTPOS);
+}
+
+#[test]
+fn en_passant_only_when_legal() {
+    // No black pawn can capture on e3
+    let mut board = Board::startpos();
+    play(&mut board, &["e2e4"]);
+
+    assert_eq!(board.to_fen(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+    assert_eq!(board.to_fen_with(EnPassantMode::Legal), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+
+    // The capture exists
+    let board = Board::from_fen(FENS[5]).unwrap();
+    assert_eq!(board.to_fen_with(EnPassantMode::Legal), FENS[5]);
+
+    // The capture exists but would expose the king along the rank
+    let board = Board::from_fen("8/8/8/K2pP2r/8/8/8/7k w - d6 0 1").unwrap();
+    assert_eq!(board.to_fen_with(EnPassantMode::Legal), "8/8/8/K2pP2r/8/8/8/7k w - - 0 1");
+}
+
+#[test]
+fn strict_accepts_legal_positions() {
+    for fen in &FENS[..7] {
+        assert!(Board::from_fen_strict(fen).is_ok(), "{}", fen);
+    }
+}
+
+#[test]
+fn strict_rejects_illegal_positions() {
+    use jesschess::error::ChessError::*;
+
+    let cases = [
+        ("rnbqkbnr/pppppppp/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "ranks"),
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPP/RNBQKBNR w KQkq - 0 1", "short rank"),
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPPP/RNBQKBNR w KQkq - 0 1", "long rank"),
+        ("8/8/8/8/8/8/8/4K3 w - - 0 1", "no black king"),
+        ("4k3/8/8/8/8/8/8/3KK3 w - - 0 1", "two white kings"),
+        ("4k2P/8/8/8/8/8/8/4K3 w - - 0 1", "pawn on eighth"),
+        ("4k3/8/8/8/8/8/8/p3K3 w - - 0 1", "pawn on first"),
+        ("4k3/8/8/8/8/8/8/4K3 w K - 0 1", "castling without rook"),
+        ("4k3/8/8/8/8/8/8/3K3R w K - 0 1", "castling without king"),
+        ("4k3/8/8/3pP3/8/8/8/4K3 w - d5 0 1", "ep rank"),
+        ("4k3/8/8/4P3/8/8/8/4K3 w - d6 0 1", "ep pawn"),
+        ("4k3/8/8/8/8/8/8/4K2r b - - 0 1", "opponent in check"),
+    ];
+
+    for (fen, case) in cases {
+        let Err(err) = Board::from_fen_strict(fen) else {
+            panic!("{}: accepted {}", case, fen);
+        };
+
+        let expected = match case {
+            "ranks" => matches!(err, InvalidRankCount { ranks: 7 }),
+            "short rank" => matches!(err, InvalidRankLength { rank: 2, files: 7 }),
+            "long rank" => matches!(err, InvalidRankLength { rank: 2, files: 9 }),
+            "no black king" => matches!(err, InvalidKingCount { side: 1, count: 0 }),
+            "two white kings" => matches!(err, InvalidKingCount { side: 0, count: 2 }),
+            "pawn on eighth" | "pawn on first" => matches!(err, PawnOnBackRank { .. }),
+            "castling without rook" | "castling without king" => matches!(err, InvalidCastlingRight { right: 'K' }),
+            "ep rank" => matches!(err, InvalidEnPassantRank { .. }),
+            "ep pawn" => matches!(err, EnPassantWithoutPawn { .. }),
+            "opponent in check" => matches!(err, OpponentInCheck { side: 0 }),
+            _ => unreachable!(),
+        };
+
+        assert!(expected, "{}: {}", case, err);
+
+        // Lenient parsing still accepts everything with a well formed placement and an
+        // en passant square on the third or sixth rank
+        if !matches!(err, InvalidRankCount { .. } | InvalidRankLength { .. } | InvalidEnPassantRank { .. }) {
+            assert!(Board::from_fen(fen).is_ok(), "{}", fen);
+        }
+    }
+}
+
+#[test]
+fn lenient_rejects_en_passant_off_the_third_and_sixth_ranks() {
+    use jesschess::error::ChessError::*;
+
+    // The pawn behind a1 or h8 would be off the board
+    for fen in ["4k3/8/8/8/8/8/8/4K3 w - a1 0 1", "4k3/8/8/8/8/8/8/4K3 b - h8 0 1", "4k3/8/8/3pP3/8/8/8/4K3 w - d5 0 1"] {
+        assert!(matches!(Board::from_fen(fen), Err(InvalidEnPassantRank { .. })), "{}", fen);
+    }
+
+    // Either side's square is accepted, with no pawn behind it to take
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - e3 0 1").unwrap();
+    assert_eq!(board.generate_legal_moves().len(), 5);
+}
@@ -0,0 +1,118 @@
+use jesschess::board::Board;
+use jesschess::error::ChessError;
+use jesschess::game::GameTree;
+use jesschess::pgn::{PgnGame, PgnReader};
+
+fn sans(tree: &GameTree, ids: &[jesschess::game::NodeId]) -> Vec<String> {
+    ids.iter()
+        .map(|&id| {
+            let board = tree.board_at(tree.node(id).parent().unwrap());
+            board.to_san(tree.node(id).mv().unwrap())
+        })
+        .collect()
+}
+
+fn read_tree(pgn: &str) -> GameTree {
+    PgnReader::new(pgn.as_bytes()).next().unwrap().unwrap().to_tree().unwrap()
+}
+
+#[test]
+fn plays_and_navigates() {
+    let mut tree = GameTree::default();
+    let root = tree.root();
+
+    let e4 = tree.play(Board::startpos().parse_san("e4").unwrap()).unwrap();
+    let e5 = tree.add_san(e4, "e5").unwrap();
+    let c5 = tree.add_san(e4, "c5").unwrap();
+
+    assert_eq!(tree.current(), e4);
+    assert_eq!(tree.node(e4).children(), [e5, c5]);
+    assert_eq!(tree.add_san(e4, "e5").unwrap(), e5);
+
+    assert!(matches!(tree.add_san(e4, "e4"), Err(ChessError::IllegalMove { .. })));
+
+    assert!(tree.forward());
+    assert_eq!(tree.current(), e5);
+    assert!(!tree.forward());
+
+    assert!(tree.back());
+    assert!(tree.enter_variation(1));
+    assert_eq!(tree.current(), c5);
+    assert!(!tree.enter_variation(0));
+
+    assert!(tree.back() && tree.back());
+    assert_eq!(tree.current(), root);
+    assert!(!tree.back());
+
+    assert_eq!(tree.board_at(c5).to_fen(), "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq c6 0 2");
+    tree.go_to(c5);
+    assert_eq!(tree.board().to_fen(), tree.board_at(c5).to_fen());
+    assert_eq!(tree.moves_to(c5).len(), 2);
+}
+
+#[test]
+fn promotes_demotes_and_deletes_variations() {
+    let mut tree = read_tree("1. e4 e5 (1... c5 2. Nf3 (2. c3)) (1... e6) 2. Nf3 *");
+    let e4 = tree.main_line(tree.root())[0];
+
+    assert_eq!(sans(&tree, tree.node(e4).children()), ["e5", "c5", "e6"]);
+
+    let c5 = tree.node(e4).children()[1];
+    assert!(tree.promote_variation(c5));
+    assert_eq!(sans(&tree, tree.node(e4).children()), ["c5", "e5", "e6"]);
+    assert!(!tree.promote_variation(c5));
+
+    assert!(tree.demote_variation(c5));
+    assert!(tree.demote_variation(c5));
+    assert!(!tree.demote_variation(c5));
+    assert_eq!(sans(&tree, tree.node(e4).children()), ["e5", "e6", "c5"]);
+
+    // The line through 2. c3 becomes the main line all the way up
+    let c3 = tree.node(c5).children()[1];
+    tree.promote_to_main_line(c3);
+    assert_eq!(sans(&tree, &tree.main_line(tree.root())), ["e4", "c5", "c3"]);
+
+    tree.go_to(c3);
+    assert!(tree.delete_variation(c5));
+    assert_eq!(tree.current(), e4);
+    assert_eq!(sans(&tree, &tree.main_line(tree.root())), ["e4", "e5", "Nf3"]);
+    assert!(!tree.delete_variation(tree.root()));
+}
+
+#[test]
+fn round_trips_through_pgn() {
+    let pgn = "{Start} 1. e4 $1 {[%eval 0.17] [%clk 0:03:00] Best} 1... e5 (1... c5 {Sicilian} 2. Nf3) 2. Nf3 *";
+    let tree = read_tree(pgn);
+
+    let e4 = tree.main_line(tree.root())[0];
+    assert_eq!(tree.node(e4).comments_before, ["Start"]);
+    assert_eq!(tree.node(e4).nags, [1]);
+    assert_eq!(tree.node(e4).comments, ["Best"]);
+    assert_eq!(tree.node(e4).annotation("eval"), Some("0.17"));
+    assert_eq!(tree.node(e4).annotation("clk"), Some("0:03:00"));
+
+    let game = PgnGame::from_tree(&tree);
+    let written = game.to_pgn().unwrap();
+
+    let movetext = written.lines().skip(8).collect::<Vec<_>>().join(" ");
+    assert_eq!(movetext.trim(), pgn);
+    assert_eq!(PgnGame::from_tree(&read_tree(&written)), game);
+}
+
+#[test]
+fn keeps_other_annotations_and_the_starting_position() {
+    let board = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+    let mut tree = GameTree::new(board.clone());
+
+    let e4 = tree.play(board.parse_san("e4").unwrap()).unwrap();
+    tree.node_mut(e4).set_annotation("csl", "Ge4");
+    tree.node_mut(e4).set_annotation("csl", "Re4");
+
+    let game = PgnGame::from_tree(&tree);
+    assert_eq!(game.tag("SetUp"), Some("1"));
+    assert_eq!(game.tag("FEN"), Some("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1"));
+    assert_eq!(game.moves[0].comments, ["[%csl Re4]"]);
+
+    let pgn = game.to_pgn().unwrap();
+    assert!(pgn.contains("1. e4 {[%csl Re4]} *"), "{}", pgn);
+}
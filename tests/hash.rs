@@ -0,0 +1,106 @@
+use std::collections::HashSet;
+
+use jesschess::board::Board;
+
+fn play(board: &mut Board, moves: &[&str]) {
+    for uci in moves {
+        let mv = board.parse_uci_move(uci).unwrap();
+        board.make_move(mv);
+    }
+}
+
+#[test]
+fn transpositions_hash_the_same() {
+    let mut a = Board::startpos();
+    let mut b = Board::startpos();
+
+    play(&mut a, &["g1f3", "g8f6", "b1c3", "b8c6"]);
+    play(&mut b, &["b1c3", "b8c6", "g1f3", "g8f6"]);
+
+    assert_eq!(a.hash(), b.hash());
+    assert!(a == b);
+
+    // Only the position counts, not the clocks
+    let c = Board::from_fen(&a.to_fen().replace(" 4 3", " 0 1")).unwrap();
+    assert_eq!(a.hash(), c.hash());
+    assert!(a == c);
+}
+
+#[test]
+fn hash_matches_fen() {
+    let mut board = Board::startpos();
+    play(&mut board, &["e2e4", "d7d5", "e4e5", "f7f5", "e5f6", "e8f7", "f6g7", "f7g6", "g7h8q"]);
+
+    assert_eq!(board.hash(), Board::from_fen(&board.to_fen()).unwrap().hash());
+
+    let mut board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    play(&mut board, &["e1c1", "e8g8"]);
+
+    assert_eq!(board.hash(), Board::from_fen(&board.to_fen()).unwrap().hash());
+}
+
+#[test]
+fn side_castling_and_en_passant_change_the_hash() {
+    let fens = [
+        "r3k2r/8/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1",
+        "r3k2r/8/8/3pP3/8/8/8/R3K2R b KQkq d6 0 1",
+        "r3k2r/8/8/3pP3/8/8/8/R3K2R w Qkq d6 0 1",
+        "r3k2r/8/8/3pP3/8/8/8/R3K2R w KQkq - 0 1",
+    ];
+
+    let hashes = fens.iter()
+        .map(|fen| Board::from_fen(fen).unwrap().hash())
+        .collect::<HashSet<_>>();
+
+    assert_eq!(hashes.len(), fens.len());
+}
+
+#[test]
+fn en_passant_only_counts_when_a_pawn_can_take() {
+    // No black pawn next to e4
+    let a = Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1").unwrap();
+    let b = Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1").unwrap();
+    assert_eq!(a.hash(), b.hash());
+
+    // ...dxe3 is possible, even though it would be illegal with the king on a4
+    let a = Board::from_fen("8/8/8/8/k2pP2R/8/8/4K3 b - e3 0 1").unwrap();
+    let b = Board::from_fen("8/8/8/8/k2pP2R/8/8/4K3 b - - 0 1").unwrap();
+    assert_ne!(a.hash(), b.hash());
+
+    // Made incrementally the same as from scratch
+    let mut board = Board::from_fen("4k3/8/8/8/3p4/8/4P1P1/4K3 w - - 0 1").unwrap();
+    play(&mut board, &["e2e4"]);
+    assert_ne!(board.hash(), Board::from_fen(&board.to_fen().replace(" e3 ", " - ")).unwrap().hash());
+    play(&mut board, &["e8d7", "g2g4"]);
+    assert_eq!(board.hash(), Board::from_fen(&board.to_fen().replace(" g3 ", " - ")).unwrap().hash());
+}
+
+#[test]
+fn unmake_restores_the_hash() {
+    let mut board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+    let hash = board.hash();
+
+    for mv in board.generate_legal_moves() {
+        board.make_move(mv);
+        assert_ne!(board.hash(), hash);
+        board.unmake_move();
+        assert_eq!(board.hash(), hash);
+    }
+}
+
+#[test]
+fn boards_dedupe_in_a_set() {
+    let mut positions = HashSet::new();
+    let mut board = Board::startpos();
+
+    positions.insert(board.clone());
+
+    // Knights out and back repeats the start position
+    for uci in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+        play(&mut board, &[uci]);
+        positions.insert(board.clone());
+    }
+
+    assert_eq!(positions.len(), 4);
+    assert!(positions.contains(&Board::startpos()));
+}
@@ -0,0 +1,148 @@
+use jesschess::board::Board;
+
+fn play(board: &mut Board, moves: &[&str]) {
+    for uci in moves {
+        let mv = board.parse_uci_move(uci).unwrap();
+        board.make_move(mv);
+    }
+}
+
+fn after(fen: &str, moves: &[&str]) -> String {
+    let mut board = Board::from_fen(fen).unwrap();
+    play(&mut board, moves);
+
+    board.to_fen()
+}
+
+#[test]
+fn updates_side_clocks_and_move_stack() {
+    let mut board = Board::startpos();
+
+    play(&mut board, &["g1f3"]);
+    assert_eq!(board.to_fen(), "rnbqkbnr/pppppppp/8/8/8/5N2/PPPPPPPP/RNBQKB1R b KQkq - 1 1");
+
+    play(&mut board, &["g8f6"]);
+    assert_eq!(board.fullmove_counter, 2);
+    assert_eq!(board.halfmove_clock, 2);
+
+    // Pawn moves and captures reset the halfmove clock
+    play(&mut board, &["e2e4"]);
+    assert_eq!(board.halfmove_clock, 0);
+    play(&mut board, &["f6e4"]);
+    assert_eq!(board.to_fen(), "rnbqkb1r/pppppppp/8/8/4n3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 0 3");
+
+    assert_eq!(board.move_stack.len(), 4);
+    assert_eq!(board.move_stack[3].to_algebraic(), "f6e4");
+}
+
+#[test]
+fn double_push_sets_the_en_passant_square() {
+    let mut board = Board::startpos();
+
+    play(&mut board, &["e2e4"]);
+    assert_eq!(board.en_passant, Some("e3".parse().unwrap()));
+    assert_eq!(board.to_fen(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+
+    // Only until the next move
+    play(&mut board, &["g8f6"]);
+    assert_eq!(board.en_passant, None);
+    assert_eq!(board.to_fen(), "rnbqkb1r/pppppppp/5n2/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 1 2");
+}
+
+#[test]
+fn en_passant_removes_the_victim() {
+    assert_eq!(
+        after("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3", &["e5f6"]),
+        "rnbqkbnr/ppp1p1pp/5P2/3p4/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 3",
+    );
+
+    assert_eq!(
+        after("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1", &["d4e3"]),
+        "4k3/8/8/8/8/4p3/8/4K3 w - - 0 2",
+    );
+}
+
+#[test]
+fn promotion_swaps_in_the_piece() {
+    assert_eq!(after("r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1", &["b7b8n"]), "rN2k3/8/8/8/8/8/8/4K3 b - - 0 1");
+    assert_eq!(after("r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1", &["b7a8q"]), "Q3k3/8/8/8/8/8/8/4K3 b - - 0 1");
+    assert_eq!(after("4k3/8/8/8/8/8/p7/1R2K3 b - - 0 1", &["a2b1r"]), "4k3/8/8/8/8/8/8/1r2K3 w - - 0 2");
+}
+
+#[test]
+fn castling_moves_the_rook() {
+    let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+
+    assert_eq!(after(fen, &["e1g1"]), "r3k2r/8/8/8/8/8/8/R4RK1 b kq - 1 1");
+    assert_eq!(after(fen, &["e1c1"]), "r3k2r/8/8/8/8/8/8/2KR3R b kq - 1 1");
+    assert_eq!(after(fen, &["e1g1", "e8c8"]), "2kr3r/8/8/8/8/8/8/R4RK1 w - - 2 2");
+    assert_eq!(after(fen, &["e1c1", "e8g8"]), "r4rk1/8/8/8/8/8/8/2KR3R w - - 2 2");
+}
+
+#[test]
+fn castling_rights_follow_king_and_rooks() {
+    let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+
+    assert_eq!(after(fen, &["e1e2"]), "r3k2r/8/8/8/8/8/4K3/R6R b kq - 1 1");
+    assert_eq!(after(fen, &["h1h2"]), "r3k2r/8/8/8/8/8/7R/R3K3 b Qkq - 1 1");
+    assert_eq!(after(fen, &["a1a2", "h8h7"]), "r3k3/7r/8/8/8/8/R7/4K2R w Kq - 2 2");
+
+    // Capturing a rook on its home square takes away its right too
+    assert_eq!(after(fen, &["a1a8"]), "R3k2r/8/8/8/8/8/8/4K2R b Kk - 0 1");
+    assert_eq!(after(fen, &["h1h8"]), "r3k2R/8/8/8/8/8/8/R3K3 b Qq - 0 1");
+    assert_eq!(after(fen, &["e1d1", "h8h1"]), "r3k3/8/8/8/8/8/8/R2K3r w q - 0 2");
+}
+
+#[test]
+fn unmake_restores_fen_and_hash() {
+    for fen in [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        "r3k3/1P6/8/8/8/8/6p1/4K2R b K - 7 40",
+    ] {
+        let mut board = Board::from_fen(fen).unwrap();
+
+        // Every move and every reply, taken back one at a time
+        for mv in board.generate_legal_moves() {
+            let before = (board.to_fen(), board.hash());
+            board.make_move(mv);
+
+            for reply in board.generate_legal_moves() {
+                let after = (board.to_fen(), board.hash());
+                board.make_move(reply);
+
+                assert_eq!(board.unmake_move(), Some(reply));
+                assert_eq!((board.to_fen(), board.hash()), after, "{} {}", mv.to_algebraic(), reply.to_algebraic());
+            }
+
+            assert_eq!(board.unmake_move(), Some(mv));
+            assert_eq!((board.to_fen(), board.hash()), before, "{}", mv.to_algebraic());
+        }
+
+        assert!(board == Board::from_fen(fen).unwrap());
+        assert!(board.move_stack.is_empty() && board.undo_stack.is_empty());
+        assert_eq!(board.unmake_move(), None);
+    }
+}
+
+#[test]
+fn unmake_takes_back_a_whole_game() {
+    let mut board = Board::startpos();
+    let moves = ["e2e4", "d7d5", "e4d5", "g8f6", "f1b5", "c7c6", "d5c6", "d8d2", "b1d2", "e7e5",
+        "c6b7", "b8d7", "b7a8q", "f8c5", "g1f3", "e8g8", "e1g1"];
+
+    let mut positions = Vec::new();
+
+    for uci in moves {
+        positions.push((board.to_fen(), board.hash()));
+        play(&mut board, &[uci]);
+    }
+
+    assert_eq!(board.to_fen(), "Q1b2rk1/p2n1ppp/5n2/1Bb1p3/8/5N2/PPPN1PPP/R1BQ1RK1 b - - 4 9");
+
+    while let Some(mv) = board.unmake_move() {
+        assert_eq!(Some((board.to_fen(), board.hash())), positions.pop(), "{}", mv.to_algebraic());
+    }
+
+    assert!(positions.is_empty());
+}
@@ -0,0 +1,102 @@
+use jesschess::board::{WHITE_BISHOP, WHITE_KING, WHITE_KNIGHT, WHITE_QUEEN, WHITE_ROOK};
+use jesschess::error::ChessError;
+use jesschess::r#move::{Move, MoveFlag};
+
+#[test]
+fn flags_round_trip() {
+    for code in 0..16 {
+        match MoveFlag::from_u16(code) {
+            Ok(flag) => assert_eq!(flag.to_u16(), code),
+            Err(e) => {
+                assert!(matches!(code, 6 | 7), "{}", code);
+                assert!(matches!(e, ChessError::InvalidMoveFlag { flag } if flag == code));
+            },
+        }
+    }
+
+    assert!(MoveFlag::from_u16(16).is_err());
+}
+
+#[test]
+fn flag_predicates() {
+    use MoveFlag::*;
+
+    let all = [
+        Quiet, DoublePawnPush, KingCastle, QueenCastle, Capture, EpCapture,
+        KnightPromotion, BishopPromotion, RookPromotion, QueenPromotion,
+        KnightPromotionCapture, BishopPromotionCapture, RookPromotionCapture, QueenPromotionCapture,
+    ];
+
+    let captures = [Capture, EpCapture, KnightPromotionCapture, BishopPromotionCapture, RookPromotionCapture, QueenPromotionCapture];
+
+    for flag in all {
+        assert_eq!(flag.is_capture(), captures.contains(&flag), "{:?}", flag);
+        assert_eq!(flag.is_castle(), matches!(flag, KingCastle | QueenCastle), "{:?}", flag);
+        assert_eq!(flag.is_promotion(), flag.promotion_piece().is_some(), "{:?}", flag);
+    }
+
+    assert_eq!(KnightPromotion.promotion_piece(), Some(WHITE_KNIGHT));
+    assert_eq!(BishopPromotionCapture.promotion_piece(), Some(WHITE_BISHOP));
+    assert_eq!(RookPromotion.promotion_piece(), Some(WHITE_ROOK));
+    assert_eq!(QueenPromotionCapture.promotion_piece(), Some(WHITE_QUEEN));
+    assert_eq!(EpCapture.promotion_piece(), None);
+}
+
+#[test]
+fn promotion_flags() {
+    for piece in [WHITE_KNIGHT, WHITE_BISHOP, WHITE_ROOK, WHITE_QUEEN] {
+        for capture in [false, true] {
+            let flag = MoveFlag::promotion(piece, capture).unwrap();
+
+            assert_eq!(flag.promotion_piece(), Some(piece));
+            assert_eq!(flag.is_capture(), capture);
+        }
+    }
+
+    assert_eq!(MoveFlag::promotion(WHITE_KING, false), None);
+}
+
+#[test]
+fn encoding_round_trips() {
+    for raw in 0..=u16::MAX {
+        let flag = raw >> 12;
+
+        let Ok(mv) = Move::from_raw(raw) else {
+            assert!(matches!(flag, 6 | 7), "{:#06x}", raw);
+            continue;
+        };
+
+        assert_eq!(mv.to_u16(), raw);
+        assert_eq!(mv.from().to_u16(), raw & 0x3f);
+        assert_eq!(mv.to().to_u16(), raw >> 6 & 0x3f);
+        assert_eq!(mv.flag().to_u16(), flag);
+        assert_eq!(Move::new(mv.from(), mv.to(), mv.flag()), mv);
+
+        assert_eq!(mv.is_capture(), mv.flag().is_capture());
+        assert_eq!(mv.is_promotion(), mv.flag().is_promotion());
+        assert_eq!(mv.is_castle(), mv.flag().is_castle());
+        assert_eq!(mv.promotion_piece(), mv.flag().promotion_piece());
+    }
+}
+
+#[test]
+fn new_puts_from_in_the_low_bits() {
+    let mv = Move::new("e2".parse().unwrap(), "e4".parse().unwrap(), MoveFlag::DoublePawnPush);
+
+    assert_eq!(mv.to_u16(), 12 | 28 << 6 | 1 << 12);
+    assert_eq!(mv.to_algebraic(), "e2e4");
+}
+
+#[test]
+fn algebraic_round_trips() {
+    for text in ["e2e4", "g1f3", "a7a8q", "h2h1n", "b7c8r", "d2d1b"] {
+        assert_eq!(Move::from_algebraic_simple(text).unwrap().to_algebraic(), text);
+    }
+
+    assert_eq!(Move::from_algebraic_simple("e7e8q").unwrap().flag(), MoveFlag::QueenPromotion);
+    assert_eq!(Move::from_algebraic_simple("e1g1").unwrap().flag(), MoveFlag::Quiet);
+
+    for text in ["e2", "e2e9", "e7e8k", "e7e8qq", "e2é4"] {
+        assert!(Move::from_algebraic_simple(text).is_err(), "{}", text);
+    }
+}
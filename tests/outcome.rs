@@ -0,0 +1,133 @@
+use jesschess::board::{Board, BLACK, WHITE};
+use jesschess::outcome::Outcome;
+
+fn play(board: &mut Board, sans: &[&str]) {
+    for san in sans {
+        let mv = board.parse_san(san).unwrap();
+        board.make_move(mv);
+    }
+}
+
+#[test]
+fn checkmate_and_stalemate() {
+    let mut board = Board::startpos();
+    play(&mut board, &["f3", "e5", "g4", "Qh4#"]);
+
+    let outcome = board.outcome().unwrap();
+    assert_eq!(outcome, Outcome::Checkmate { winner: BLACK });
+    assert_eq!(outcome.winner(), Some(BLACK));
+    assert_eq!(outcome.result(), "0-1");
+
+    let board = Board::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+    assert_eq!(board.outcome(), Some(Outcome::Stalemate));
+    assert_eq!(Outcome::Stalemate.result(), "1/2-1/2");
+
+    assert_eq!(Board::startpos().outcome(), None);
+}
+
+#[test]
+fn mate_comes_before_the_move_rules() {
+    let board = Board::from_fen("7k/6Q1/6K1/8/8/8/8/8 b - - 150 100").unwrap();
+    assert_eq!(board.outcome(), Some(Outcome::Checkmate { winner: WHITE }));
+}
+
+#[test]
+fn fifty_and_seventy_five_move_rules() {
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 99 80").unwrap();
+    assert_eq!(board.outcome(), None);
+
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 100 80").unwrap();
+    assert_eq!(board.outcome(), Some(Outcome::FiftyMoves));
+    assert!(!Outcome::FiftyMoves.is_automatic());
+
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 150 80").unwrap();
+    assert_eq!(board.outcome(), Some(Outcome::SeventyFiveMoves));
+    assert!(Outcome::SeventyFiveMoves.is_automatic());
+}
+
+#[test]
+fn insufficient_material() {
+    for fen in [
+        "4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/4KB2 w - - 0 1",
+        "4k3/8/8/8/8/8/8/4KN2 b - - 0 1",
+        // Bishops all on dark squares, c1 f8 and a7
+        "4kb2/b7/8/8/8/8/8/2B1K3 w - - 0 1",
+    ] {
+        let board = Board::from_fen(fen).unwrap();
+        assert_eq!(board.outcome(), Some(Outcome::InsufficientMaterial), "{}", fen);
+    }
+
+    for fen in [
+        "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/3NKN2 w - - 0 1",
+        "4kn2/8/8/8/8/8/8/4KN2 w - - 0 1",
+        // Bishops on opposite colours, c1 and c8
+        "2b1k3/8/8/8/8/8/8/2B1K3 w - - 0 1",
+    ] {
+        let board = Board::from_fen(fen).unwrap();
+        assert!(!board.is_insufficient_material(), "{}", fen);
+    }
+}
+
+#[test]
+fn threefold_and_fivefold_repetition() {
+    let mut board = Board::startpos();
+    let shuffle = ["Nf3", "Nf6", "Ng1", "Ng8"];
+
+    play(&mut board, &shuffle);
+    assert_eq!(board.repetitions(), 1);
+    assert_eq!(board.outcome(), None);
+
+    play(&mut board, &shuffle);
+    assert_eq!(board.repetitions(), 2);
+    assert_eq!(board.outcome(), Some(Outcome::ThreefoldRepetition));
+
+    play(&mut board, &shuffle);
+    play(&mut board, &shuffle);
+    assert_eq!(board.outcome(), Some(Outcome::FivefoldRepetition));
+
+    // A pawn move starts the count again
+    play(&mut board, &["e4"]);
+    assert_eq!(board.repetitions(), 0);
+    assert_eq!(board.outcome(), None);
+}
+
+#[test]
+fn repetitions_need_the_same_side_to_move() {
+    let start = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+    let mut board = start.clone();
+
+    // The rook comes back in three moves, so Black is to move this time
+    play(&mut board, &["Ra3", "Kd8", "Ra2", "Ke8", "Ra1"]);
+
+    assert_eq!(board.to_fen().split(' ').next(), start.to_fen().split(' ').next());
+    assert_eq!(board.repetitions(), 0);
+
+    play(&mut board, &["Kd8", "Ra2", "Ke8", "Ra1"]);
+    assert_eq!(board.repetitions(), 1);
+}
+
+#[test]
+fn repetitions_after_a_double_push() {
+    // Nothing can take on e3, so the position after 1. e4 comes up again
+    let mut board = Board::startpos();
+    let shuffle = ["Nf6", "Nf3", "Ng8", "Ng1"];
+
+    play(&mut board, &["e4"]);
+    play(&mut board, &shuffle);
+    assert_eq!(board.repetitions(), 1);
+
+    play(&mut board, &shuffle);
+    assert_eq!(board.outcome(), Some(Outcome::ThreefoldRepetition));
+
+    // Here ...dxe3 is possible straight after e4 and not once the knights have moved
+    let mut board = Board::from_fen("4k1n1/8/8/8/3p4/8/4P3/4K1N1 w - - 0 1").unwrap();
+
+    play(&mut board, &["e4"]);
+    play(&mut board, &shuffle);
+    assert_eq!(board.repetitions(), 0);
+
+    play(&mut board, &shuffle);
+    assert_eq!(board.repetitions(), 1);
+}
@@ -0,0 +1,89 @@
+use jesschess::board::{Board, FEN_STARTPOS};
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+const POSITION_3: &str = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+const POSITION_4: &str = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
+const POSITION_4_MIRRORED: &str = "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1";
+const POSITION_5: &str = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8";
+const POSITION_6: &str = "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10";
+
+/// Published node counts from https://www.chessprogramming.org/Perft_Results, indexed by depth
+const STARTPOS_NODES: [u64; 7] = [1, 20, 400, 8_902, 197_281, 4_865_609, 119_060_324];
+const KIWIPETE_NODES: [u64; 6] = [1, 48, 2_039, 97_862, 4_085_603, 193_690_690];
+const POSITION_3_NODES: [u64; 8] = [1, 14, 191, 2_812, 43_238, 674_624, 11_030_083, 178_633_661];
+const POSITION_4_NODES: [u64; 6] = [1, 6, 264, 9_467, 422_333, 15_833_292];
+const POSITION_5_NODES: [u64; 6] = [1, 44, 1_486, 62_379, 2_103_487, 89_941_194];
+const POSITION_6_NODES: [u64; 6] = [1, 46, 2_079, 89_890, 3_894_594, 164_075_551];
+
+/// Unoptimised builds are far too slow for the deeper counts, so they only run the
+/// shallow end of each suite
+fn max_depth(debug: u32, release: u32) -> u32 {
+    if cfg!(debug_assertions) { debug } else { release }
+}
+
+fn check(fen: &str, expected: &[u64], max_depth: u32) {
+    let mut board = Board::from_fen(fen).unwrap();
+
+    for depth in 0..=max_depth {
+        assert_eq!(board.perft(depth), expected[depth as usize], "{} depth {}", fen, depth);
+    }
+}
+
+#[test]
+fn perft_startpos() {
+    check(FEN_STARTPOS, &STARTPOS_NODES, max_depth(4, 5));
+}
+
+#[test]
+fn perft_kiwipete() {
+    check(KIWIPETE, &KIWIPETE_NODES, max_depth(3, 4));
+}
+
+#[test]
+fn perft_position_3() {
+    check(POSITION_3, &POSITION_3_NODES, max_depth(5, 6));
+}
+
+#[test]
+fn perft_position_4() {
+    check(POSITION_4, &POSITION_4_NODES, max_depth(3, 5));
+}
+
+#[test]
+fn perft_position_4_mirrored() {
+    check(POSITION_4_MIRRORED, &POSITION_4_NODES, max_depth(3, 5));
+}
+
+#[test]
+fn perft_position_5() {
+    check(POSITION_5, &POSITION_5_NODES, max_depth(3, 4));
+}
+
+#[test]
+fn perft_position_6() {
+    check(POSITION_6, &POSITION_6_NODES, max_depth(3, 4));
+}
+
+#[test]
+fn perft_restores_position() {
+    let mut board = Board::from_fen(KIWIPETE).unwrap();
+    let before = board.to_string();
+
+    board.perft(3);
+
+    assert_eq!(board.to_string(), before);
+    assert!(board.move_stack.is_empty());
+}
+
+#[test]
+fn divide_sums_to_perft() {
+    let mut board = Board::from_fen(KIWIPETE).unwrap();
+    let mut out = Vec::new();
+
+    let total = board.divide(2, &mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+
+    assert_eq!(total, KIWIPETE_NODES[2]);
+    assert_eq!(out.lines().count(), 48);
+    assert!(out.lines().any(|line| line == "e1g1: 43"));
+}
@@ -0,0 +1,306 @@
+use std::io::{BufReader, Read};
+
+use jesschess::error::ChessError;
+use jesschess::pgn::{PgnGame, PgnReader};
+
+fn read_all(pgn: &str) -> Vec<Result<PgnGame, ChessError>> {
+    PgnReader::new(pgn.as_bytes()).collect()
+}
+
+fn sans(moves: &[jesschess::pgn::PgnMove]) -> Vec<&str> {
+    moves.iter().map(|mv| mv.san.as_str()).collect()
+}
+
+#[test]
+fn reads_tags_and_moves() {
+    let pgn = r#"[Event "F/S Return Match"]
+[Site "Belgrade, Serbia JUG"]
+[Round "29"]
+[White "Fischer, Robert J."]
+[Black "Spassky, Boris V."]
+[Annotator "Someone \"quoted\" \\ here"]
+[Result "1/2-1/2"]
+
+1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 4.Ba4 Nf6 5. O-O Be7 6. Re1 b5 7. Bb3 d6 8. c3
+O-O 9. h3 Nb8 10. d4 Nbd7 1/2-1/2
+"#;
+
+    let games = read_all(pgn);
+    assert_eq!(games.len(), 1);
+
+    let game = games[0].as_ref().unwrap();
+
+    assert_eq!(game.tags.len(), 7);
+    assert_eq!(game.tag("Site"), Some("Belgrade, Serbia JUG"));
+    assert_eq!(game.tag("Annotator"), Some(r#"Someone "quoted" \ here"#));
+    assert_eq!(game.tag("ECO"), None);
+
+    assert_eq!(game.moves.len(), 20);
+    assert_eq!(sans(&game.moves[..4]), ["e4", "e5", "Nf3", "Nc6"]);
+    assert_eq!(game.moves[8].san, "O-O");
+    assert_eq!(game.moves[19].san, "Nbd7");
+    assert_eq!(game.result.as_deref(), Some("1/2-1/2"));
+}
+
+#[test]
+fn reads_comments_nags_and_variations() {
+    let pgn = "{Opening comment} 1. e4 $1 {Best by test} e5!? 2. Nf3 (2. f4 {The gambit} exf4 (2... d5) 3. Nf3) \
+               (2. Nc3) 2... Nc6 ; to the end of the line\n3. Bb5?! a6 *";
+
+    let game = read_all(pgn).remove(0).unwrap();
+
+    assert_eq!(sans(&game.moves), ["e4", "e5", "Nf3", "Nc6", "Bb5", "a6"]);
+    assert_eq!(game.result.as_deref(), Some("*"));
+
+    let e4 = &game.moves[0];
+    assert_eq!(e4.comments_before, ["Opening comment"]);
+    assert_eq!(e4.nags, [1]);
+    assert_eq!(e4.comments, ["Best by test"]);
+
+    assert_eq!(game.moves[1].nags, [5]);
+    assert_eq!(game.moves[4].nags, [6]);
+    assert_eq!(game.moves[3].comments, ["to the end of the line"]);
+
+    let nf3 = &game.moves[2];
+    assert_eq!(nf3.variations.len(), 2);
+    assert_eq!(sans(&nf3.variations[0]), ["f4", "exf4", "Nf3"]);
+    assert_eq!(sans(&nf3.variations[1]), ["Nc3"]);
+    assert_eq!(nf3.variations[0][0].comments, ["The gambit"]);
+
+    // Variations nest
+    assert_eq!(sans(&nf3.variations[0][1].variations[0]), ["d5"]);
+}
+
+#[test]
+fn reads_several_games() {
+    let pgn = "[Event \"One\"]\n\n1. d4 d5 1-0\n\n\
+               [Event \"Two\"]\n\n1. c4 0-1\n\
+               [Event \"Three\"]\n1. Nf3\n\
+               [Event \"Four\"]\n\n% an escaped line\n1. g3 {multi\nline comment} g6";
+
+    let games: Vec<_> = read_all(pgn).into_iter().map(Result::unwrap).collect();
+
+    let events: Vec<_> = games.iter().map(|game| game.tag("Event").unwrap()).collect();
+    assert_eq!(events, ["One", "Two", "Three", "Four"]);
+
+    assert_eq!(games[1].result.as_deref(), Some("0-1"));
+
+    // Without termination markers
+    assert_eq!(sans(&games[2].moves), ["Nf3"]);
+    assert_eq!(games[2].result, None);
+    assert_eq!(games[3].moves[0].comments, ["multi\nline comment"]);
+    assert_eq!(sans(&games[3].moves), ["g3", "g6"]);
+}
+
+#[test]
+fn reports_errors_and_carries_on() {
+    let pgn = "[Event \"Good\"]\n\n1. e4 e5 1-0\n\n\
+               [Event \"Bad\"]\n\n1. e4 e5 2. Nf3 Xx9 3. Bb5 1-0\n\n\
+               [Event \"Bad tag]\n[Site \"?\"]\n\n1. d4 1-0\n\n\
+               [Event \"Unmatched\"]\n\n1. e4 e5) 1-0\n\
+               [Event \"Unterminated\"]\n\n1. e4 (1. d4 d5\n\n\
+               [Event \"Also good\"]\n\n1. c4 *\n";
+
+    let games = read_all(pgn);
+    assert_eq!(games.len(), 6);
+
+    assert_eq!(games[0].as_ref().unwrap().tag("Event"), Some("Good"));
+
+    let errors: Vec<_> = games[1..5].iter().map(|game| match game {
+        Err(ChessError::InvalidPgn { line, column, .. }) => (*line, *column),
+        other => panic!("Expected an error, got {:?}", other),
+    }).collect();
+
+    assert_eq!(errors[0], (7, 17));
+    assert_eq!(errors[1], (9, 17));
+    assert_eq!(errors[2], (16, 9));
+    assert_eq!(errors[3], (21, 1));
+
+    let Err(err) = &games[4] else { panic!() };
+    assert!(err.to_string().contains("Unterminated variation"), "{}", err);
+
+    assert_eq!(games[5].as_ref().unwrap().tag("Event"), Some("Also good"));
+}
+
+#[test]
+fn rejects_malformed_movetext() {
+    for pgn in ["1. e4 (e5", "1. e4 () e5", "( e4 )", "1. e4 $x", "1. e4 !!! e5", "1. e4 {unterminated", "1. e4 (1-0)"] {
+        assert!(matches!(read_all(pgn)[0], Err(ChessError::InvalidPgn { .. })), "{}", pgn);
+    }
+}
+
+/// Endless PGN, one game after another
+struct Endless(std::iter::Cycle<std::vec::IntoIter<u8>>);
+
+impl Read for Endless {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        for (byte, next) in buf.iter_mut().zip(&mut self.0) {
+            *byte = next;
+        }
+
+        Ok(buf.len())
+    }
+}
+
+#[test]
+fn streams_games() {
+    let game = b"[Event \"Again\"]\n\n1. e4 e5 2. Nf3 Nc6 1-0\n\n".to_vec();
+    let reader = PgnReader::new(BufReader::new(Endless(game.into_iter().cycle())));
+
+    let games: Vec<_> = reader.take(1000).map(Result::unwrap).collect();
+
+    assert_eq!(games.len(), 1000);
+    assert!(games.iter().all(|game| game.moves.len() == 4));
+}
+
+/// Fails every read and write
+struct Broken;
+
+impl Read for Broken {
+    fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+        Err(std::io::Error::new(std::io::ErrorKind::BrokenPipe, "gone"))
+    }
+}
+
+impl std::io::Write for Broken {
+    fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+        Err(std::io::Error::new(std::io::ErrorKind::BrokenPipe, "gone"))
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn keeps_io_errors() {
+    use std::error::Error;
+
+    let mut reader = PgnReader::new(BufReader::new(Broken));
+    let Some(Err(ChessError::Io(e))) = reader.next() else { panic!("expected an I/O error") };
+    assert_eq!(e.kind(), std::io::ErrorKind::BrokenPipe);
+    assert!(reader.next().is_none());
+
+    let err = jesschess::pgn::write_game(&mut Broken, &read_one("1. e4 *")).unwrap_err();
+    assert!(matches!(&err, ChessError::Io(e) if e.kind() == std::io::ErrorKind::BrokenPipe));
+    assert_eq!(err.to_string(), "I/O error: gone");
+    assert!(err.source().is_some());
+}
+
+fn read_one(pgn: &str) -> PgnGame {
+    PgnReader::new(pgn.as_bytes()).next().unwrap().unwrap()
+}
+
+#[test]
+fn writes_export_format() {
+    let game = read_one("[Result \"1-0\"]\n[ECO \"C20\"]\n[Black \"Bob\"]\n[Annotator \"A \\\"quote\\\"\"]\n\n\
+                         1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7# 1-0");
+
+    assert_eq!(game.to_pgn().unwrap(), "\
+[Event \"?\"]
+[Site \"?\"]
+[Date \"????.??.??\"]
+[Round \"?\"]
+[White \"?\"]
+[Black \"Bob\"]
+[Result \"1-0\"]
+[Annotator \"A \\\"quote\\\"\"]
+[ECO \"C20\"]
+
+1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7# 1-0
+
+");
+}
+
+#[test]
+fn writes_move_numbers_after_interruptions() {
+    let game = read_one("1. e4 {King's pawn} e5 2. Nf3 (2. f4 exf4) (2. Nc3 $2) Nc6 $1 3. Bb5 *");
+
+    let movetext = game.to_pgn().unwrap().lines().skip(8).collect::<Vec<_>>().join(" ");
+    assert_eq!(movetext.trim(), "1. e4 {King's pawn} 1... e5 2. Nf3 (2. f4 exf4) (2. Nc3 $2) 2... Nc6 $1 3. Bb5 *");
+
+    // A variation on a black move, and a game starting with Black to move
+    let game = read_one("[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 40\"]\n\n40... Kd7 (40... Ke7 41. Kd2) 41. e4 *");
+
+    let pgn = game.to_pgn().unwrap();
+    assert!(pgn.contains("40... Kd7 (40... Ke7 41. Kd2) 41. e4 *"), "{}", pgn);
+}
+
+#[test]
+fn writes_san_from_the_board() {
+    let game = read_one("[FEN \"4k3/1P6/8/8/8/8/8/R3K1NR w K - 0 1\"]\n\n1. Ng1f3 Kd7 2. b8Q Kc6 3. O-O *");
+
+    let pgn = game.to_pgn().unwrap();
+    assert!(pgn.contains("1. Nf3 Kd7 2. b8=Q Kc6 3. O-O *"), "{}", pgn);
+
+    let game = read_one("1. e4 e5 2. Ke3 *");
+    assert!(matches!(game.to_pgn(), Err(ChessError::IllegalMove { .. })));
+}
+
+#[test]
+fn wraps_at_80_columns() {
+    let mut movetext = String::new();
+
+    for _ in 0..10 {
+        movetext.push_str("Nf3 {a long comment which goes on and on} Nf6 Ng1 Ng8 ");
+    }
+
+    let pgn = read_one(&format!("{} *", movetext)).to_pgn().unwrap();
+
+    assert!(pgn.lines().all(|line| line.len() <= 80));
+    assert!(pgn.lines().any(|line| line.len() > 70));
+    // Long comments may be broken across lines, which only changes their whitespace
+    let reread = read_one(&pgn);
+    assert_eq!(sans(&reread.moves), sans(&read_one(&format!("{} *", movetext)).moves));
+    assert!(reread.moves.iter()
+        .flat_map(|mv| &mv.comments)
+        .all(|comment| comment.split_whitespace().collect::<Vec<_>>().join(" ") == "a long comment which goes on and on"));
+}
+
+#[test]
+fn writes_comments_that_read_back() {
+    let mut game = read_one("1. e4 e5 *");
+    game.moves[0].comments.push("a {nested} comment}".to_string());
+
+    let pgn = game.to_pgn().unwrap();
+    assert!(pgn.contains("1. e4 {a {nested comment} 1... e5 *"), "{}", pgn);
+    assert_eq!(sans(&read_one(&pgn).moves), ["e4", "e5"]);
+
+    // Wrapped by characters, not bytes
+    let mut movetext = String::new();
+
+    for _ in 0..10 {
+        movetext.push_str("Nf3 {ça déborde toujours un peu plus à côté} Nf6 Ng1 Ng8 ");
+    }
+
+    let pgn = read_one(&format!("{} *", movetext)).to_pgn().unwrap();
+
+    assert!(pgn.lines().all(|line| line.chars().count() <= 80));
+    assert!(pgn.lines().any(|line| line.len() > 80));
+}
+
+#[test]
+fn clock_and_eval_commands() {
+    use jesschess::pgn::PgnEval;
+    use std::time::Duration;
+
+    let game = read_one("1. e4 {[%eval 0.17] [%clk 1:02:03]} e5 {[%clk 0:00:09.5] [%eval #-3] Oops [%csl Ga1]} \
+                         2. Nf3 {[%eval -0.05]} *");
+
+    let e4 = &game.moves[0];
+    assert_eq!(e4.eval, Some(PgnEval::Centipawns(17)));
+    assert_eq!(e4.clock, Some(Duration::from_secs(3723)));
+    assert!(e4.comments.is_empty());
+
+    let e5 = &game.moves[1];
+    assert_eq!(e5.eval, Some(PgnEval::Mate(-3)));
+    assert_eq!(e5.clock, Some(Duration::from_millis(9500)));
+    assert_eq!(e5.comments, ["Oops [%csl Ga1]"]);
+
+    assert_eq!(game.moves[2].eval, Some(PgnEval::Centipawns(-5)));
+
+    let pgn = game.to_pgn().unwrap();
+    let movetext = pgn.lines().skip(8).collect::<Vec<_>>().join(" ");
+    assert!(movetext.contains("1. e4 {[%eval 0.17] [%clk 1:02:03]} 1... e5 {[%eval #-3] [%clk 0:00:09.5] Oops [%csl Ga1]} 2. Nf3 {[%eval -0.05]} *"), "{}", pgn);
+    assert_eq!(read_one(&pgn).moves, game.moves);
+}
@@ -0,0 +1,98 @@
+use jesschess::board::Board;
+use jesschess::error::ChessError;
+
+const FENS: [&str; 5] = [
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+    "7k/8/8/8/8/1Q3Q2/8/1Q2K3 w - - 0 1",
+];
+
+fn san(fen: &str, uci: &str) -> String {
+    let board = Board::from_fen(fen).unwrap();
+    let mv = board.generate_legal_moves()
+        .into_iter()
+        .find(|mv| mv.to_algebraic() == uci)
+        .unwrap();
+
+    board.to_san(mv)
+}
+
+#[test]
+fn round_trip_every_legal_move() {
+    for fen in FENS {
+        let board = Board::from_fen(fen).unwrap();
+
+        for mv in board.generate_legal_moves() {
+            let san = board.to_san(mv);
+            assert_eq!(board.parse_san(&san).unwrap(), mv, "{} in {}", san, fen);
+        }
+    }
+}
+
+#[test]
+fn formats_san() {
+    assert_eq!(san(FENS[0], "e1g1"), "O-O");
+    assert_eq!(san(FENS[0], "e1c1"), "O-O-O");
+    assert_eq!(san(FENS[0], "d5e6"), "dxe6");
+    assert_eq!(san(FENS[0], "e5f7"), "Nxf7");
+    assert_eq!(san("r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7a8q"), "bxa8=Q+");
+    assert_eq!(san("r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7b8n"), "b8=N");
+    assert_eq!(san(FENS[2], "d7c8n"), "dxc8=N");
+    assert_eq!(san(FENS[3], "f1e1"), "Rfe1");
+    assert_eq!(san(FENS[3], "a1e1"), "Rae1");
+
+    // File, rank and full square disambiguation
+    assert_eq!(san(FENS[4], "b3d3"), "Qb3d3");
+    assert_eq!(san(FENS[4], "b1d3"), "Q1d3");
+    assert_eq!(san(FENS[4], "b1c2"), "Q1c2");
+    assert_eq!(san(FENS[4], "b3d1"), "Qb3d1");
+    assert_eq!(san(FENS[4], "f3d1"), "Qfd1");
+
+    // Check and mate
+    assert_eq!(san(FENS[4], "b3b8"), "Qb8+");
+    assert_eq!(san("6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1", "a1a8"), "Ra8#");
+}
+
+#[test]
+fn parses_san() {
+    let board = Board::startpos();
+
+    assert_eq!(board.parse_san("Nf3").unwrap().to_algebraic(), "g1f3");
+    assert_eq!(board.parse_san("e4").unwrap().to_algebraic(), "e2e4");
+    assert_eq!(board.parse_san("e4!?").unwrap().to_algebraic(), "e2e4");
+
+    let board = Board::from_fen(FENS[0]).unwrap();
+    assert!(board.parse_san("O-O").unwrap().is_castle());
+    assert!(board.parse_san("0-0-0").unwrap().is_castle());
+    assert_eq!(board.parse_san("Nxf7").unwrap().to_algebraic(), "e5f7");
+
+    let board = Board::from_fen("r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(board.parse_san("bxa8=Q").unwrap().to_algebraic(), "b7a8q");
+    assert_eq!(board.parse_san("bxa8N").unwrap().to_algebraic(), "b7a8n");
+
+    let board = Board::from_fen("R2r2k1/8/8/8/8/8/8/3RK3 w - - 0 1").unwrap();
+    assert_eq!(board.parse_san("Raxd8+").unwrap().to_algebraic(), "a8d8");
+    assert_eq!(board.parse_san("Rdxd8").unwrap().to_algebraic(), "d1d8");
+    assert!(matches!(board.parse_san("Rxd8"), Err(ChessError::AmbiguousMove { .. })));
+}
+
+#[test]
+fn rejects_bad_san() {
+    let board = Board::startpos();
+
+    assert!(matches!(board.parse_san("e5"), Err(ChessError::IllegalMove { .. })));
+    assert!(matches!(board.parse_san("O-O"), Err(ChessError::IllegalMove { .. })));
+    assert!(matches!(board.parse_san("Zf3"), Err(ChessError::InvalidSan { .. })));
+    assert!(matches!(board.parse_san("N"), Err(ChessError::InvalidSan { .. })));
+    assert!(matches!(board.parse_san("Nf9"), Err(ChessError::InvalidSan { .. })));
+    assert!(matches!(board.parse_san("e2e4=K"), Err(ChessError::InvalidSan { .. })));
+    assert!(matches!(board.parse_san("Nxf3"), Err(ChessError::InvalidSan { .. })));
+
+    let board = Board::from_fen(FENS[3]).unwrap();
+    assert!(matches!(board.parse_san("Re1"), Err(ChessError::AmbiguousMove { .. })));
+
+    let board = Board::from_fen(FENS[0]).unwrap();
+    assert!(matches!(board.parse_san("Nf7"), Err(ChessError::InvalidSan { .. })));
+}
@@ -0,0 +1,114 @@
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration, Instant};
+
+use jesschess::board::Board;
+use jesschess::search::{search, SearchLimits, MATE};
+use jesschess::tt::TranspositionTable;
+
+fn search_depth(fen: &str, depth: u32) -> jesschess::search::SearchResult {
+    let mut board = Board::from_fen(fen).unwrap();
+    let limits = SearchLimits { depth: Some(depth), ..Default::default() };
+
+    search(&mut board, &mut TranspositionTable::default(), &limits, &AtomicBool::new(false), |_| {})
+}
+
+#[test]
+fn finds_mate_in_one() {
+    let result = search_depth("6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1", 3);
+
+    assert_eq!(result.best_move.unwrap().to_algebraic(), "a1a8");
+    assert_eq!(result.score, MATE - 1);
+    assert_eq!(result.mate_in(), Some(1));
+}
+
+#[test]
+fn finds_mate_in_two() {
+    // 1. Re8+ Rxe8 2. Rxe8#
+    let result = search_depth("3r2k1/5ppp/8/8/8/8/4RPPP/4R1K1 w - - 0 1", 4);
+
+    assert_eq!(result.best_move.unwrap().to_algebraic(), "e2e8");
+
+    assert_eq!(result.mate_in(), Some(2));
+    assert_eq!(result.pv.len(), 3);
+}
+
+#[test]
+fn sees_getting_mated() {
+    // Whatever White plays, Qxg2#
+    let result = search_depth("8/8/8/8/4q3/8/5kPP/7K w - - 0 1", 3);
+
+    assert_eq!(result.mate_in(), Some(-1));
+    assert_eq!(result.score, -MATE + 2);
+}
+
+#[test]
+fn mate_beats_the_fifty_move_rule() {
+    // Ra8# is the hundredth half-move without a capture or pawn move
+    let result = search_depth("6k1/5ppp/8/8/8/8/8/R3K3 w - - 99 80", 3);
+
+    assert_eq!(result.best_move.unwrap().to_algebraic(), "a1a8");
+    assert_eq!(result.mate_in(), Some(1));
+
+    // Without the mate every move draws
+    let result = search_depth("6k1/8/8/8/8/8/8/R3K3 w - - 99 80", 3);
+    assert_eq!(result.score, 0);
+}
+
+#[test]
+fn wins_material() {
+    // The queen is hanging
+    let result = search_depth("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1", 2);
+    assert_eq!(result.best_move.unwrap().to_algebraic(), "d1d5");
+}
+
+#[test]
+fn sees_past_the_horizon() {
+    // Qxe5+ wins a pawn at depth 1 unless the recapture dxe5 is seen
+    let result = search_depth("4k3/8/3p4/4p3/8/8/8/4QK2 w - - 0 1", 1);
+    assert_ne!(result.best_move.unwrap().to_algebraic(), "e1e5");
+}
+
+#[test]
+fn no_moves() {
+    let result = search_depth("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", 3);
+
+    assert_eq!(result.best_move, None);
+    assert_eq!(result.score, 0);
+}
+
+#[test]
+fn respects_limits() {
+    let mut board = Board::startpos();
+    let before = board.to_fen();
+
+    let limits = SearchLimits { nodes: Some(5_000), ..Default::default() };
+    let result = search(&mut board, &mut TranspositionTable::default(), &limits, &AtomicBool::new(false), |_| {});
+    assert!(result.nodes <= 5_000);
+    assert!(result.best_move.is_some());
+
+    let start = Instant::now();
+    let limits = SearchLimits { time: Some(Duration::from_millis(50)), ..Default::default() };
+    let result = search(&mut board, &mut TranspositionTable::default(), &limits, &AtomicBool::new(false), |_| {});
+    assert!(start.elapsed() < Duration::from_millis(500));
+    assert!(result.best_move.is_some());
+
+    // A search stopped before it starts still has a move to play
+    let result = search(&mut board, &mut TranspositionTable::default(), &SearchLimits::default(), &AtomicBool::new(true), |_| {});
+    assert!(result.best_move.is_some());
+
+    assert_eq!(board.to_fen(), before);
+}
+
+#[test]
+fn reports_each_iteration() {
+    let mut board = Board::startpos();
+    let limits = SearchLimits { depth: Some(3), ..Default::default() };
+    let mut depths = Vec::new();
+
+    let result = search(&mut board, &mut TranspositionTable::default(), &limits, &AtomicBool::new(false), |result| depths.push(result.depth));
+
+    assert_eq!(depths, [1, 2, 3]);
+    assert_eq!(result.depth, 3);
+    assert_eq!(result.pv.len(), 3);
+    assert_eq!(result.best_move, result.pv.first().copied());
+}
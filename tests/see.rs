@@ -0,0 +1,50 @@
+use jesschess::board::Board;
+
+fn see(fen: &str, uci: &str) -> i32 {
+    let board = Board::from_fen(fen).unwrap();
+    let mv = board.parse_uci_move(uci).unwrap();
+
+    board.see(mv)
+}
+
+#[test]
+fn undefended_capture() {
+    assert_eq!(see("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e1e5"), 100);
+}
+
+#[test]
+fn defended_capture_loses() {
+    assert_eq!(see("1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1", "d3e5"), 100 - 320);
+}
+
+#[test]
+fn quiet_move() {
+    assert_eq!(see("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e1e4"), 0);
+}
+
+#[test]
+fn x_ray_attackers() {
+    // The rook behind joins in, so Black can't recapture
+    assert_eq!(see("3rk3/8/8/3p4/8/8/3R4/3RK3 w - - 0 1", "d2d5"), 100);
+
+    // Both sides have a battery, White comes off a rook down
+    assert_eq!(see("3rk3/3r4/8/3p4/8/8/3R4/3RK3 w - - 0 1", "d2d5"), -400);
+
+    // A queen behind a bishop
+    assert_eq!(see("4k3/8/2n5/8/8/8/6B1/7Q w - - 0 1", "g2c6"), 320);
+    assert_eq!(see("4k3/1p6/2n5/8/8/8/6B1/7Q w - - 0 1", "g2c6"), 320 - 330 + 100);
+}
+
+#[test]
+fn king_cannot_capture_defended_piece() {
+    assert_eq!(see("8/8/8/8/8/3k4/3p4/3R3K w - - 0 1", "d1d2"), 100 - 500);
+    assert_eq!(see("8/8/8/8/8/3k4/3p4/3RK3 w - - 0 1", "d1d2"), 100);
+}
+
+#[test]
+fn en_passant_and_promotion() {
+    assert_eq!(see("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6"), 100);
+    assert_eq!(see("4k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7a8q"), 800);
+    assert_eq!(see("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7a8q"), 800 - 900);
+    assert_eq!(see("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7b8q"), 500 + 800);
+}
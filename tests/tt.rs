@@ -0,0 +1,92 @@
+use jesschess::board::Board;
+use jesschess::search::MATE;
+use jesschess::tt::{Bound, TranspositionTable};
+
+fn some_move() -> jesschess::r#move::Move {
+    Board::startpos().parse_uci_move("e2e4").unwrap()
+}
+
+#[test]
+fn sized_in_megabytes() {
+    let tt = TranspositionTable::new(1);
+    assert_eq!(tt.size_mb(), 1);
+    assert!(tt.len() > 10_000);
+
+    let mut tt = TranspositionTable::new(4);
+    assert_eq!(tt.size_mb(), 4);
+
+    tt.store(42, 3, Bound::Exact, 10, None, 0);
+    tt.resize(2);
+    assert_eq!(tt.size_mb(), 2);
+    assert!(tt.probe(42).is_none());
+}
+
+#[test]
+fn stores_and_probes() {
+    let mut tt = TranspositionTable::new(1);
+    let mv = some_move();
+
+    assert!(tt.probe(42).is_none());
+
+    tt.store(42, 5, Bound::Lower, 123, Some(mv), 3);
+
+    let entry = tt.probe(42).unwrap();
+    assert_eq!(entry.depth(), 5);
+    assert_eq!(entry.bound(), Bound::Lower);
+    assert_eq!(entry.score(3), 123);
+    assert_eq!(entry.best_move(), Some(mv));
+
+    tt.clear();
+    assert!(tt.probe(42).is_none());
+}
+
+#[test]
+fn mate_scores_are_relative_to_the_position() {
+    let mut tt = TranspositionTable::new(1);
+
+    // Mate found 4 plies below a position at ply 3, so mate in 7 from the root
+    tt.store(42, 5, Bound::Exact, MATE - 7, None, 3);
+
+    assert_eq!(tt.probe(42).unwrap().score(3), MATE - 7);
+    // Reached by a shorter path it's a shorter mate
+    assert_eq!(tt.probe(42).unwrap().score(1), MATE - 5);
+
+    tt.store(42, 5, Bound::Exact, -MATE + 7, None, 3);
+    assert_eq!(tt.probe(42).unwrap().score(1), -MATE + 5);
+}
+
+#[test]
+fn prefers_depth_then_age() {
+    // Small enough that every key lands in the same slot
+    let mut tt = TranspositionTable::new(0);
+    assert_eq!(tt.len(), 1);
+
+    tt.store(1, 6, Bound::Exact, 0, None, 0);
+    tt.store(2, 4, Bound::Exact, 0, None, 0);
+    assert!(tt.probe(1).is_some());
+
+    tt.store(2, 6, Bound::Exact, 0, None, 0);
+    assert!(tt.probe(2).is_some());
+
+    // An entry from an earlier search goes whatever its depth
+    tt.new_search();
+    tt.store(3, 1, Bound::Exact, 0, None, 0);
+    assert!(tt.probe(3).is_some());
+
+    // The same position is always updated, keeping its move
+    tt.store(3, 1, Bound::Exact, 0, Some(some_move()), 0);
+    tt.store(3, 0, Bound::Upper, 0, None, 0);
+    assert_eq!(tt.probe(3).unwrap().best_move(), Some(some_move()));
+}
+
+#[test]
+fn hashfull_counts_the_current_search() {
+    let mut tt = TranspositionTable::new(0);
+    assert_eq!(tt.hashfull(), 0);
+
+    tt.store(1, 1, Bound::Exact, 0, None, 0);
+    assert_eq!(tt.hashfull(), 1000);
+
+    tt.new_search();
+    assert_eq!(tt.hashfull(), 0);
+}
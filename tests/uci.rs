@@ -0,0 +1,140 @@
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use jesschess::uci::{GoParams, Uci};
+
+/// Output shared with the engine's search thread
+#[derive(Clone, Default)]
+struct Output(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn session(input: &str) -> Vec<String> {
+    let out = Output::default();
+    Uci::new(out.clone()).run(input.as_bytes()).unwrap();
+
+    let out = out.0.lock().unwrap();
+    String::from_utf8(out.clone()).unwrap().lines().map(str::to_string).collect()
+}
+
+#[test]
+fn handshake() {
+    let out = session("uci\nisready\nquit\n");
+
+    assert!(out[0].starts_with("id name jesschess"));
+    assert!(out.contains(&"uciok".to_string()));
+    assert!(out.iter().any(|line| line.starts_with("option name Hash type spin")));
+    assert_eq!(out.last().unwrap(), "readyok");
+}
+
+#[test]
+fn hash_option() {
+    let out = session("setoption name Hash value 1
+setoption name Hash value lots
+setoption name Foo value 1
+isready
+");
+
+    assert_eq!(out, [
+        "info string Invalid Hash value: lots",
+        "info string Unknown option: Foo",
+        "readyok",
+    ]);
+}
+
+#[test]
+fn go_replies_with_legal_bestmove() {
+    let out = session("position startpos moves e2e4 e7e5 g1f3\ngo depth 1\n");
+
+    let bestmove = out.last().unwrap().strip_prefix("bestmove ").unwrap();
+    assert!(out.iter().any(|line| line.starts_with("info ")));
+
+    let mut board = jesschess::board::Board::startpos();
+    for mv in ["e2e4", "e7e5", "g1f3"] {
+        let mv = board.parse_uci_move(mv).unwrap();
+        board.make_move(mv);
+    }
+
+    assert!(board.parse_uci_move(bestmove).is_ok());
+}
+
+#[test]
+fn search_finishes_at_end_of_input() {
+    let out = session("position startpos\ngo depth 5\n");
+
+    assert!(out.iter().any(|line| line.starts_with("info depth 5 ")), "{:?}", out);
+    assert!(out.iter().all(|line| !line.starts_with("info depth") || line.contains(" hashfull ")));
+    assert!(out.last().unwrap().starts_with("bestmove "));
+}
+
+#[test]
+fn position_fen_with_moves() {
+    let out = session("position fen 7k/8/8/8/8/8/8/R3K3 w Q - 0 1 moves e1c1 h8g8\ngo movetime 10\n");
+    assert!(out.last().unwrap().starts_with("bestmove "));
+
+    let out = session("position fen 7k/8/8/8/8/8/8/R3K3 w Q - 0 1 moves e1e3\n");
+    assert!(out[0].starts_with("info string Illegal move"));
+}
+
+#[test]
+fn infinite_waits_for_stop() {
+    let out = session("go infinite\nisready\nstop\n");
+
+    let ready = out.iter().position(|line| line == "readyok").unwrap();
+    let bestmove = out.iter().position(|line| line.starts_with("bestmove")).unwrap();
+
+    assert!(ready < bestmove);
+    assert_eq!(bestmove, out.len() - 1);
+}
+
+#[test]
+fn ponder_waits_for_ponderhit() {
+    let out = session("go ponder movetime 10\nisready\nponderhit\nisready\n");
+
+    let ready = out.iter().position(|line| line == "readyok").unwrap();
+    let bestmove = out.iter().position(|line| line.starts_with("bestmove")).unwrap();
+
+    assert!(ready < bestmove);
+}
+
+#[test]
+fn finds_mate() {
+    let out = session("position fen 6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1\ngo depth 3\n");
+
+    assert!(out.iter().any(|line| line.contains("score mate 1")));
+    assert_eq!(out.last().unwrap(), "bestmove a1a8");
+}
+
+#[test]
+fn no_legal_moves() {
+    let out = session("position fen 7k/5Q2/6K1/8/8/8/8/8 b - - 0 1\ngo\n");
+    assert_eq!(out.last().unwrap(), "bestmove 0000");
+}
+
+#[test]
+fn parses_go_params() {
+    let params = GoParams::parse(&["wtime", "1000", "btime", "2000", "winc", "10", "binc", "20", "movestogo", "5"]);
+
+    assert_eq!(params.wtime, Some(Duration::from_millis(1000)));
+    assert_eq!(params.btime, Some(Duration::from_millis(2000)));
+    assert_eq!(params.winc, Some(Duration::from_millis(10)));
+    assert_eq!(params.binc, Some(Duration::from_millis(20)));
+    assert_eq!(params.movestogo, Some(5));
+    assert!(!params.infinite);
+
+    let params = GoParams::parse(&["depth", "7", "nodes", "1000", "movetime", "50", "infinite"]);
+
+    assert_eq!(params.depth, Some(7));
+    assert_eq!(params.nodes, Some(1000));
+    assert_eq!(params.movetime, Some(Duration::from_millis(50)));
+    assert!(params.infinite);
+}
@@ -0,0 +1,49 @@
+use jesschess::board::Board;
+use jesschess::error::ChessError;
+use jesschess::r#move::MoveFlag;
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+#[test]
+fn sets_move_flags() {
+    let board = Board::startpos();
+    assert_eq!(board.parse_uci_move("e2e4").unwrap().flag(), MoveFlag::DoublePawnPush);
+    assert_eq!(board.parse_uci_move("g1f3").unwrap().flag(), MoveFlag::Quiet);
+
+    let board = Board::from_fen(KIWIPETE).unwrap();
+    assert_eq!(board.parse_uci_move("e5f7").unwrap().flag(), MoveFlag::Capture);
+    assert_eq!(board.parse_uci_move("e1g1").unwrap().flag(), MoveFlag::KingCastle);
+    assert_eq!(board.parse_uci_move("e1c1").unwrap().flag(), MoveFlag::QueenCastle);
+
+    let board = Board::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3").unwrap();
+    assert_eq!(board.parse_uci_move("e5f6").unwrap().flag(), MoveFlag::EpCapture);
+
+    let board = Board::from_fen("r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(board.parse_uci_move("b7a8q").unwrap().flag(), MoveFlag::QueenPromotionCapture);
+    assert_eq!(board.parse_uci_move("b7b8n").unwrap().flag(), MoveFlag::KnightPromotion);
+}
+
+#[test]
+fn accepts_king_takes_rook_castling() {
+    let board = Board::from_fen(KIWIPETE).unwrap();
+
+    assert_eq!(board.parse_uci_move("e1h1").unwrap(), board.parse_uci_move("e1g1").unwrap());
+    assert_eq!(board.parse_uci_move("e1a1").unwrap(), board.parse_uci_move("e1c1").unwrap());
+
+    // Without the right it's still not a legal move
+    let board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w Qkq - 0 1").unwrap();
+    assert!(matches!(board.parse_uci_move("e1h1"), Err(ChessError::IllegalMove { .. })));
+}
+
+#[test]
+fn rejects_illegal_and_malformed_moves() {
+    let board = Board::startpos();
+
+    assert!(matches!(board.parse_uci_move("e2e5"), Err(ChessError::IllegalMove { .. })));
+    assert!(matches!(board.parse_uci_move("e3e4"), Err(ChessError::IllegalMove { .. })));
+    assert!(matches!(board.parse_uci_move("e7e5"), Err(ChessError::IllegalMove { .. })));
+    assert!(matches!(board.parse_uci_move("e2e4q"), Err(ChessError::IllegalMove { .. })));
+    assert!(matches!(board.parse_uci_move("e2"), Err(ChessError::InvalidAlgNotation { .. })));
+    assert!(matches!(board.parse_uci_move("i2i4"), Err(ChessError::InvalidAlgNotation { .. })));
+    assert!(matches!(board.parse_uci_move("e2e4k"), Err(ChessError::InvalidAlgNotation { .. })));
+}
@@ -0,0 +1,240 @@
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use jesschess::xboard::XBoard;
+
+/// Output shared with the engine's search thread
+#[derive(Clone, Default)]
+struct Output(Arc<Mutex<Vec<u8>>>);
+
+impl Output {
+    fn lines(&self) -> Vec<String> {
+        let out = self.0.lock().unwrap();
+        String::from_utf8(out.clone()).unwrap().lines().map(str::to_string).collect()
+    }
+
+    /// Waits until the output so far matches, failing the test after a while
+    fn wait_for(&self, matches: impl Fn(&[String]) -> bool) -> Vec<String> {
+        let start = Instant::now();
+
+        loop {
+            let lines = self.lines();
+
+            if matches(&lines) {
+                return lines;
+            }
+
+            assert!(start.elapsed() < Duration::from_secs(60), "timed out: {:?}", lines);
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn session(input: &str) -> Vec<String> {
+    let out = Output::default();
+    XBoard::new(out.clone()).run(input.as_bytes()).unwrap();
+
+    out.lines()
+}
+
+/// Centiseconds in a line of thinking output
+fn thinking_time(line: &str) -> Option<u64> {
+    line.split(' ').nth(2)?.parse().ok()
+}
+
+#[test]
+fn feature_negotiation() {
+    let out = session("xboard\nprotover 2\nping 7\n");
+
+    assert!(out[0].starts_with("feature "));
+    assert!(out[0].contains("usermove=1") && out[0].contains("setboard=1"));
+    assert!(out[1].contains("option=\"OwnBook -check 0\""));
+    assert_eq!(out[2], "feature done=1");
+    assert_eq!(out[3], "pong 7");
+}
+
+#[test]
+fn engine_replies_to_usermove() {
+    let out = session("new\nsd 2\nusermove e2e4\n");
+    assert!(out.last().unwrap().starts_with("move "));
+
+    // In force mode moves are only recorded
+    let out = session("new\nforce\nusermove e2e4\nusermove e7e5\n");
+    assert!(out.is_empty());
+
+    let out = session("new\nusermove e2e5\n");
+    assert_eq!(out, ["Illegal move: e2e5"]);
+}
+
+#[test]
+fn go_plays_side_to_move() {
+    let out = session("new\nsd 2\nforce\nusermove e2e4\nusermove e7e5\ngo\n");
+    assert!(out.last().unwrap().starts_with("move "));
+}
+
+#[test]
+fn undo_and_remove() {
+    let out = session("new\nforce\nusermove e2e4\nundo\nusermove e2e4\nusermove e7e5\nremove\nusermove d2d4\n");
+    assert!(out.is_empty());
+}
+
+#[test]
+fn post_and_analyze_print_thinking() {
+    let out = session("new\nsd 2\npost\nusermove e2e4\n");
+    assert_eq!(out.len(), 3);
+    assert!(out[0].starts_with("1 "));
+    assert!(out[1].starts_with("2 "));
+    assert!(out[2].starts_with("move "));
+
+    let out = Output::default();
+    let mut xboard = XBoard::new(out.clone());
+
+    xboard.handle("new").unwrap();
+    xboard.handle("sd 2").unwrap();
+    xboard.handle("analyze").unwrap();
+    out.wait_for(|lines| lines.len() == 2);
+
+    // Analysis goes on in the new position
+    xboard.handle("usermove e2e4").unwrap();
+    out.wait_for(|lines| lines.len() == 4);
+    xboard.handle("exit").unwrap();
+
+    let lines = out.lines();
+    assert_eq!(lines.len(), 4);
+    assert!(lines.iter().all(|line| !line.starts_with("move")));
+}
+
+#[test]
+fn analysis_has_no_time_limit() {
+    let out = Output::default();
+    let mut xboard = XBoard::new(out.clone());
+
+    xboard.handle("analyze").unwrap();
+    out.wait_for(|lines| lines.iter().any(|line| thinking_time(line).is_some_and(|centis| centis > 150)));
+
+    let start = Instant::now();
+    xboard.handle("exit").unwrap();
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    let lines = out.lines();
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(out.lines(), lines);
+}
+
+#[test]
+fn setboard_restarts_analysis() {
+    let out = Output::default();
+    let mut xboard = XBoard::new(out.clone());
+
+    xboard.handle("analyze").unwrap();
+    out.wait_for(|lines| !lines.is_empty());
+
+    xboard.handle("setboard 6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+    let lines = out.wait_for(|lines| lines.iter().any(|line| line.ends_with(" a1a8")));
+    xboard.handle("exit").unwrap();
+
+    let mate = lines.iter().find(|line| line.ends_with(" a1a8")).unwrap();
+    assert_eq!(mate.split(' ').nth(1), Some("100001"));
+    assert!(lines.iter().all(|line| !line.starts_with("move")));
+}
+
+#[test]
+fn question_mark_moves_now() {
+    let out = Output::default();
+    let mut xboard = XBoard::new(out.clone());
+
+    xboard.handle("new").unwrap();
+    xboard.handle("st 600").unwrap();
+    xboard.handle("usermove e2e4").unwrap();
+    assert!(out.lines().is_empty());
+
+    let start = Instant::now();
+    xboard.handle("?").unwrap();
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    let lines = out.lines();
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0].starts_with("move "));
+
+    // The move is on the board, so it's White's turn again
+    xboard.handle("force").unwrap();
+    xboard.handle("usermove d2d4").unwrap();
+    assert_eq!(out.lines().len(), 1);
+}
+
+#[test]
+fn commands_interrupt_thinking() {
+    for command in ["force", "new", "exit", "quit", "setboard 4k3/8/8/8/8/8/4P3/4K3 w - - 0 1"] {
+        let out = Output::default();
+        let mut xboard = XBoard::new(out.clone());
+
+        xboard.handle("new").unwrap();
+        xboard.handle("st 600").unwrap();
+        xboard.handle("usermove e2e4").unwrap();
+
+        let start = Instant::now();
+        xboard.run(format!("{}\nping 1\n", command).as_bytes()).unwrap();
+        assert!(start.elapsed() < Duration::from_secs(5), "{}", command);
+
+        // Abandoned without a move, and `ping` answered straight away
+        let lines = out.lines();
+        assert!(lines.iter().all(|line| !line.starts_with("move")), "{}", command);
+        assert_eq!(lines.last().map(String::as_str), if command == "quit" { None } else { Some("pong 1") });
+    }
+}
+
+#[test]
+fn ping_waits_for_the_move() {
+    let out = session("new\nsd 3\nusermove e2e4\nping 3\n");
+
+    assert_eq!(out.len(), 2);
+    assert!(out[0].starts_with("move "));
+    assert_eq!(out[1], "pong 3");
+}
+
+#[test]
+fn reports_results() {
+    let out = session("setboard 7k/8/6K1/8/8/8/8/5Q2 w - - 0 1\nforce\nusermove f1f7\n");
+    assert_eq!(out, ["1/2-1/2 {Stalemate}"]);
+
+    let out = session("setboard 7k/8/6K1/8/8/8/8/R7 w - - 0 1\nforce\nusermove a1a8\n");
+    assert_eq!(out, ["1-0 {White mates}"]);
+}
+
+#[test]
+fn engine_finds_mate() {
+    let out = session("setboard 6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1\nsd 3\npost\ngo\n");
+
+    assert!(out.iter().any(|line| line.split(' ').nth(1) == Some("100001")));
+    assert_eq!(out[out.len() - 2], "move a1a8");
+    assert_eq!(out[out.len() - 1], "1-0 {White mates}");
+}
+
+#[test]
+fn time_controls() {
+    let mut xboard = XBoard::new(Output::default());
+    xboard.run("level 40 5 0\ntime 30000\notim 29000\n".as_bytes()).unwrap();
+
+    assert_eq!(xboard.time_control.moves_per_session, 40);
+    assert_eq!(xboard.time_control.base, Duration::from_secs(300));
+    assert_eq!(xboard.time_left, Duration::from_secs(300));
+    assert_eq!(xboard.opponent_time_left, Duration::from_secs(290));
+
+    xboard.run("level 0 2:30 1.5\nst 10\nsd 6\n".as_bytes()).unwrap();
+
+    assert_eq!(xboard.time_control.base, Duration::from_secs(150));
+    assert_eq!(xboard.time_control.increment, Duration::from_millis(1500));
+    assert_eq!(xboard.time_control.move_time, Some(Duration::from_secs(10)));
+    assert_eq!(xboard.time_control.depth, Some(6));
+}